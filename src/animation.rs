@@ -0,0 +1,45 @@
+use std::time::Duration;
+
+use crate::buffer::Buffer;
+use crate::cli::AnimationStyle;
+use crate::explosion::Explosion;
+use crate::palette::Palette;
+use crate::mandelbrot::{Mandelbrot, MandelbrotFast, MandelbrotMatrix};
+use crate::rainbow::Rainbow;
+use crate::waves::{Waves, WavesGradient};
+
+/// A style that draws one frame at a time into an offscreen buffer.
+pub trait Animation: Send {
+    /// Draws the frame at `elapsed` since the animation started, without any
+    /// text; the engine lays that out on top. Every cell of `buffer` is
+    /// owned by the animation, so stale content must be cleared.
+    /// The frame depends on `elapsed` alone, not on how often it is drawn, so
+    /// a paused show stands still.
+    fn render(&mut self, buffer: &mut Buffer, elapsed: Duration);
+
+    /// How long to wait between frames.
+    fn frame_interval(&self) -> Duration;
+
+    /// Trades detail for speed, from 0.0 (cheapest) to 1.0 (full detail).
+    /// Styles that are cheap to draw ignore it.
+    fn set_quality(&mut self, _quality: f32) {}
+
+    /// Draws with `palette` instead of the style's own colors. The text
+    /// keeps its color.
+    fn set_palette(&mut self, _palette: &Palette) {}
+}
+
+impl AnimationStyle {
+    /// The style's animation; `seed` drives any randomness it uses.
+    pub fn animation(&self, seed: u64) -> Box<dyn Animation> {
+        match self {
+            AnimationStyle::Rainbow => Box::new(Rainbow::default()),
+            AnimationStyle::Explosion => Box::new(Explosion::new(seed)),
+            AnimationStyle::Waves => Box::new(Waves::default()),
+            AnimationStyle::WavesGradient => Box::new(WavesGradient::default()),
+            AnimationStyle::Mandelbrot => Box::new(Mandelbrot::new()),
+            AnimationStyle::MandelbrotMatrix => Box::new(MandelbrotMatrix::new()),
+            AnimationStyle::MandelbrotFast => Box::new(MandelbrotFast::new()),
+        }
+    }
+}
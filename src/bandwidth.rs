@@ -0,0 +1,94 @@
+use std::time::Duration;
+
+use serde::{de, Deserialize, Deserializer};
+
+/// Parses a rate for `--max-bandwidth`: bytes per second, with an optional
+/// `k` or `M` suffix, e.g. `20k`.
+pub fn parse_rate(s: &str) -> Result<u64, String> {
+    let invalid = || format!("invalid bandwidth '{}', expected bytes per second like 20000 or 20k", s);
+    let trimmed = s.trim();
+    let (number, scale) = match trimmed.char_indices().last() {
+        Some((i, 'k' | 'K')) => (&trimmed[..i], 1_000.0),
+        Some((i, 'm' | 'M')) => (&trimmed[..i], 1_000_000.0),
+        _ => (trimmed, 1.0),
+    };
+    let rate = number.parse::<f64>().map_err(|_| invalid())? * scale;
+    if rate.is_finite() && rate >= 1.0 {
+        Ok(rate as u64)
+    } else {
+        Err(invalid())
+    }
+}
+
+/// `max-bandwidth = 20000` or `max-bandwidth = "20k"` in the config or a show
+/// file.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RateValue {
+    Bytes(u64),
+    Text(String),
+}
+
+/// Reads an optional rate from a file, checked as [`parse_rate`] checks it.
+pub fn deserialize_rate<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u64>, D::Error> {
+    let text = match RateValue::deserialize(deserializer)? {
+        RateValue::Bytes(bytes) => bytes.to_string(),
+        RateValue::Text(text) => text,
+    };
+    parse_rate(&text).map(Some).map_err(de::Error::custom)
+}
+
+/// How far the throttle coarsens updates: the first levels loosen the color
+/// tolerance, the last ones interlace rows.
+const MAX_LEVEL: u8 = 8;
+
+/// Keeps output under a byte rate by coarsening updates a level at a time
+/// while frames overspend the budget, and refining them again once savings
+/// build up. The budget accrues like a bucket holding up to a second's
+/// worth, so single cheap or costly frames don't swing the level.
+pub struct Throttle {
+    bytes_per_sec: u64,
+    /// Bytes that may be written right now; negative when in debt.
+    credit: f64,
+    level: u8,
+}
+
+impl Throttle {
+    pub fn new(bytes_per_sec: u64) -> Self {
+        Self {
+            bytes_per_sec,
+            credit: bytes_per_sec as f64,
+            level: 0,
+        }
+    }
+
+    /// Accounts for a frame of `bytes` just written, `elapsed` after the
+    /// one before. Taking the time from the caller keeps a seeded run, which
+    /// steps by a fixed frame time, independent of the clock.
+    pub fn spend(&mut self, bytes: u64, elapsed: Duration) {
+        let rate = self.bytes_per_sec as f64;
+        let earned = rate * elapsed.as_secs_f64();
+        self.credit = (self.credit + earned - bytes as f64).clamp(-rate, rate);
+
+        if self.credit < 0.0 {
+            self.level = (self.level + 1).min(MAX_LEVEL);
+        } else if self.credit > rate / 2.0 {
+            self.level = self.level.saturating_sub(1);
+        }
+    }
+
+    /// How far per channel colors may drift before a cell is redrawn: 0,
+    /// then 4 doubling up to 64.
+    pub fn tolerance(&self) -> u8 {
+        match self.level {
+            0 => 0,
+            level => 4 << (level - 1).min(4),
+        }
+    }
+
+    /// Update one in this many rows per frame: 1 until the color tolerance
+    /// is used up, then up to 4.
+    pub fn interlace(&self) -> u16 {
+        1 + u16::from(self.level.saturating_sub(5))
+    }
+}
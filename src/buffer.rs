@@ -0,0 +1,113 @@
+use termion::color;
+
+use crate::terminal::{Cell, Rect};
+
+const BORDER_COLOR: color::Rgb = color::Rgb(200, 200, 200);
+
+/// An offscreen grid of cells that animations draw into.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Buffer {
+    pub width: u16,
+    pub height: u16,
+    cells: Vec<Cell>,
+}
+
+impl Buffer {
+    pub fn new(width: u16, height: u16) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![Cell::default(); width as usize * height as usize],
+        }
+    }
+
+    pub fn center_pos(&self) -> (u16, u16) {
+        (self.width / 2, self.height / 2)
+    }
+
+    /// The whole buffer as a region.
+    pub fn rect(&self) -> Rect {
+        Rect::new(0, 0, self.width, self.height)
+    }
+
+    pub fn cells(&self) -> &[Cell] {
+        &self.cells
+    }
+
+    pub fn get(&self, x: u16, y: u16) -> &Cell {
+        &self.cells[self.index(x, y)]
+    }
+
+    pub fn set(&mut self, x: u16, y: u16, cell: Cell) {
+        if x < self.width && y < self.height {
+            let i = self.index(x, y);
+            self.cells[i] = cell;
+        }
+    }
+
+    /// Resets every cell to a blank space.
+    pub fn clear(&mut self) {
+        self.cells.fill(Cell::default());
+    }
+
+    /// Writes `text` starting at `(x, y)`, clipping anything past the right edge.
+    pub fn put_str(&mut self, x: u16, y: u16, text: &str, color: color::Rgb) {
+        for (i, c) in text.chars().enumerate() {
+            let Some(x) = x.checked_add(i as u16) else { break };
+            self.set(x, y, Cell { char: c, color, background: None });
+        }
+    }
+
+    /// Copies all of `src` with its top-left corner at `(x, y)`, clipping
+    /// whatever falls outside this buffer.
+    pub fn blit(&mut self, src: &Buffer, x: u16, y: u16) {
+        for sy in 0..src.height {
+            for sx in 0..src.width {
+                if let (Some(dx), Some(dy)) = (x.checked_add(sx), y.checked_add(sy)) {
+                    self.set(dx, dy, *src.get(sx, sy));
+                }
+            }
+        }
+    }
+
+    /// Draws a box along the edge of `rect`, with `title` set into the top
+    /// edge. The interior is left untouched.
+    pub fn draw_border(&mut self, rect: Rect, title: Option<&str>) {
+        self.draw_border_in(rect, title, BORDER_COLOR);
+    }
+
+    /// Like [`Buffer::draw_border`], in `color`.
+    pub fn draw_border_in(&mut self, rect: Rect, title: Option<&str>, color: color::Rgb) {
+        if rect.width < 2 || rect.height < 2 {
+            return;
+        }
+        let (right, bottom) = (rect.right() - 1, rect.bottom() - 1);
+        let line = |char| Cell { char, color, background: None };
+
+        for x in rect.x + 1..right {
+            self.set(x, rect.y, line('─'));
+            self.set(x, bottom, line('─'));
+        }
+        for y in rect.y + 1..bottom {
+            self.set(rect.x, y, line('│'));
+            self.set(right, y, line('│'));
+        }
+        self.set(rect.x, rect.y, line('┌'));
+        self.set(right, rect.y, line('┐'));
+        self.set(rect.x, bottom, line('└'));
+        self.set(right, bottom, line('┘'));
+
+        if let Some(title) = title {
+            // Keep a corner and a space of padding on each side.
+            let room = rect.width.saturating_sub(4) as usize;
+            let title: String = title.chars().take(room).collect();
+            if !title.is_empty() {
+                self.put_str(rect.x + 1, rect.y, &format!(" {} ", title), color);
+            }
+        }
+    }
+
+    fn index(&self, x: u16, y: u16) -> usize {
+        y as usize * self.width as usize + x as usize
+    }
+}
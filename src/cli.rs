@@ -1,17 +1,264 @@
-use clap::Parser;
+use std::path::PathBuf;
+
+use chrono::NaiveTime;
+use clap::{parser::ValueSource, ArgMatches, Parser, Subcommand};
+use serde::{Deserialize, Serialize};
+
+use crate::bandwidth;
+use crate::clock;
+use crate::config::{Settings, FPS_RANGE};
+use crate::layout::{self, Align, Margins, TextBlock, VAlign};
+use crate::legibility::{TextColor, TextEffect};
+use crate::motion::ReducedMotion;
+use crate::palette::Palette;
+use crate::remote::Request;
+use crate::sequence::Sequence;
+use crate::terminal::Rect;
+use crate::transition::TransitionKind;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 pub struct Args {
-    #[arg(long, value_enum)]
-    pub style: AnimationStyle,
-    #[arg(short, long, default_value = "")]
-    pub text: String,
-    #[arg(short, long, default_value = "5000", help = "Animation duration in milliseconds (infinite if 0)")]
-    pub duration: u64,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    #[arg(long, value_enum, global = true, env = "REVEAL_STYLE")]
+    pub style: Option<AnimationStyle>,
+    #[arg(short, long, global = true, env = "REVEAL_TEXT", help = "Text to reveal, or - to read it from stdin [default: \"\"]")]
+    pub text: Option<String>,
+    #[arg(long, global = true, help = "Read the text to reveal from a file (not with --text)")]
+    pub text_file: Option<PathBuf>,
+    #[arg(long, global = true, help = "Keep reading --text - or --text-file and update the text live")]
+    pub watch: bool,
+    #[arg(short, long, global = true, env = "REVEAL_DURATION", help = "Animation duration in milliseconds (infinite if 0) [default: 5000]")]
+    pub duration: Option<u64>,
+    #[arg(long, global = true, help = "Play styles in order, e.g. waves:2000,explosion:1500:text=Boom (not with --style)")]
+    pub sequence: Option<Sequence>,
+    #[arg(long, global = true, env = "REVEAL_LOOPS", help = "Times to play the sequence (infinite if 0) [default: 1]")]
+    pub loops: Option<u32>,
+    #[arg(long, value_enum, global = true, env = "REVEAL_TRANSITION", help = "Transition between sequence segments")]
+    pub transition: Option<TransitionKind>,
+    #[arg(long, global = true, env = "REVEAL_TRANSITION_DURATION", help = "Transition duration in milliseconds [default: 500]")]
+    pub transition_duration: Option<u64>,
+    #[arg(long, global = true, help = "Animate below the cursor instead of full screen, keeping the last frame")]
+    pub inline: bool,
+    #[arg(long, global = true, default_value = "8", help = "Lines to use with --inline")]
+    pub height: u16,
+    #[arg(long, global = true, value_name = "X,Y,WIDTH,HEIGHT", help = "Play inside this region of the screen")]
+    pub viewport: Option<Rect>,
+    #[arg(long, global = true, help = "Draw a border around the animation")]
+    pub border: bool,
+    #[arg(long, global = true, help = "Title set into the border (implies --border)")]
+    pub title: Option<String>,
+    #[arg(long, global = true, env = "REVEAL_PRESET", help = "Named preset from the config file")]
+    pub preset: Option<String>,
+    #[arg(long, global = true, env = "REVEAL_CONFIG", help = "Config file [default: ~/.config/reveal/config.toml]")]
+    pub config: Option<PathBuf>,
+    #[arg(long, global = true, value_name = "SOCKET", help = "Accept commands from `reveal ctl` on this Unix socket (not with screensaver or serve)")]
+    pub listen: Option<PathBuf>,
+    #[arg(
+        long,
+        global = true,
+        env = "REVEAL_REDUCED_MOTION",
+        value_name = "MODE",
+        num_args = 0..=1,
+        default_missing_value = "limit",
+        help = "Calm the animation for motion or flash sensitivity: limit (slower, no flashing) or static"
+    )]
+    pub reduced_motion: Option<ReducedMotion>,
+    #[arg(
+        long,
+        global = true,
+        env = "REVEAL_FPS",
+        value_parser = clap::value_parser!(u32).range(*FPS_RANGE.start() as i64..=*FPS_RANGE.end() as i64),
+        help = "Hold this frame rate, dropping frames and fractal detail when the machine can't keep up [default: each style's own]"
+    )]
+    pub fps: Option<u32>,
+    #[arg(
+        long,
+        global = true,
+        env = "REVEAL_PALETTE",
+        help = "Draw every style in a built-in palette: viridis, magma, ocean, sunset, neon, grayscale or corporate"
+    )]
+    pub palette: Option<Palette>,
+    #[arg(
+        long,
+        global = true,
+        env = "REVEAL_GRADIENT",
+        value_name = "COLORS",
+        value_parser = Palette::parse_gradient,
+        conflicts_with = "palette",
+        help = "Draw every style in a custom gradient, e.g. \"#ff0000,#0000ff\""
+    )]
+    pub gradient: Option<Palette>,
+    #[arg(long, global = true, env = "REVEAL_THEME", value_name = "FILE", help = "Brand colors from a design-token JSON file or CSS custom properties")]
+    pub theme: Option<PathBuf>,
+    #[arg(
+        long,
+        global = true,
+        env = "REVEAL_TEXT_COLOR",
+        value_name = "COLOR",
+        help = "Text color as #rrggbb, or auto to pick black or white per character for contrast"
+    )]
+    pub text_color: Option<TextColor>,
+    #[arg(long, global = true, env = "REVEAL_TEXT_EFFECT", help = "Set the text off from the animation")]
+    pub text_effect: Option<TextEffect>,
+    #[arg(long, value_enum, global = true, env = "REVEAL_ALIGN", help = "Where the text goes across the screen [default: center]")]
+    pub align: Option<Align>,
+    #[arg(long, value_enum, global = true, env = "REVEAL_VALIGN", help = "Where the text goes down the screen [default: middle]")]
+    pub valign: Option<VAlign>,
+    #[arg(
+        long,
+        global = true,
+        env = "REVEAL_OFFSET",
+        value_name = "X,Y",
+        value_parser = layout::parse_offset,
+        allow_hyphen_values = true,
+        help = "Move the text by this many cells, negative for left or up"
+    )]
+    pub offset: Option<(i16, i16)>,
+    #[arg(
+        long,
+        global = true,
+        env = "REVEAL_MARGIN",
+        value_name = "CELLS",
+        help = "Keep text this far from the edges: N, V,H or TOP,RIGHT,BOTTOM,LEFT"
+    )]
+    pub margin: Option<Margins>,
+    #[arg(
+        long = "block",
+        global = true,
+        value_name = "TEXT[:KEY=VALUE...]",
+        allow_hyphen_values = true,
+        help = "More text placed on its own, e.g. \"v1.2:valign=bottom:align=right\" (keys: align, valign, offset, margin); repeatable"
+    )]
+    pub blocks: Vec<TextBlock>,
+    #[arg(long, global = true, help = "Overlay FPS, render time, bytes and cells changed per frame, and print a summary to stderr on exit")]
+    pub stats: bool,
+    #[arg(
+        long,
+        global = true,
+        env = "REVEAL_MAX_BANDWIDTH",
+        value_name = "BYTES_PER_SEC",
+        value_parser = bandwidth::parse_rate,
+        help = "Keep output under this rate, e.g. 20k, by redrawing colors less precisely when over"
+    )]
+    pub max_bandwidth: Option<u64>,
+    #[arg(long, global = true, env = "REVEAL_SEED", help = "Seed for all randomness; the same seed, size and duration give identical output")]
+    pub seed: Option<u64>,
+    #[arg(long, global = true, help = "Print the text plainly instead of animating, e.g. for screen readers (automatic when stdout isn't a terminal)")]
+    pub accessible: bool,
+    #[arg(long, global = true, help = "Frame the plainly printed text in a static colored banner")]
+    pub banner: bool,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Play a show described in a TOML or JSON file
+    Play {
+        /// Path to the show file
+        file: PathBuf,
+    },
+    /// Show progress read from stdin, as lines like `42` or `42 Compiling foo`
+    Progress {
+        /// Style to play once 100 is reached
+        #[arg(long, value_enum, default_value = "explosion")]
+        outro: AnimationStyle,
+    },
+    /// Count down to a time of day or for a number of seconds
+    Countdown {
+        /// Time of day to count down to, as HH:MM or HH:MM:SS
+        #[arg(long, value_parser = clock::parse_time, required_unless_present = "seconds")]
+        until: Option<NaiveTime>,
+        /// Seconds to count down from
+        #[arg(long, conflicts_with = "until")]
+        seconds: Option<u64>,
+        /// Style to play when the countdown hits zero
+        #[arg(long, value_enum, default_value = "explosion")]
+        finale: AnimationStyle,
+    },
+    /// Show the current time
+    Clock,
+    /// Cycle through random styles and messages until a key is pressed
+    Screensaver {
+        /// Start after this many seconds without a keypress, and again after each stop
+        #[arg(long, default_value_t = 0)]
+        idle: u64,
+        /// File with one message per line, shown in turn
+        #[arg(long)]
+        messages: Option<PathBuf>,
+        /// Seconds over which the picture dims against burn-in (never if 0)
+        #[arg(long, default_value_t = 600)]
+        dim: u64,
+    },
+    /// Stream the animation to telnet clients
+    Serve {
+        /// TCP port to listen on
+        #[arg(long, default_value_t = 2323)]
+        port: u16,
+        /// Address to listen on
+        #[arg(long, default_value = "0.0.0.0")]
+        bind: String,
+    },
+    /// Send a command to a reveal started with --listen
+    Ctl {
+        /// Socket the running reveal listens on
+        #[arg(long, env = "REVEAL_SOCKET")]
+        socket: PathBuf,
+        #[command(subcommand)]
+        request: Request,
+    },
 }
 
-#[derive(clap::ValueEnum, Clone, Debug)]
+impl Args {
+    /// The settings given as flags or environment variables.
+    pub fn settings(&self) -> Settings {
+        Settings {
+            style: self.style.clone(),
+            text: self.text.clone(),
+            duration: self.duration,
+            loops: self.loops,
+            transition: self.transition,
+            transition_duration: self.transition_duration,
+            fps: self.fps,
+            palette: self.gradient.clone().or_else(|| self.palette.clone()),
+            theme: self.theme.clone(),
+            text_color: self.text_color,
+            text_effect: self.text_effect,
+            align: self.align,
+            valign: self.valign,
+            offset: self.offset,
+            margin: self.margin,
+            big_text: None,
+            inline: self.inline.then_some(self.height),
+            viewport: self.viewport,
+            border: self.border.then_some(true),
+            title: self.title.clone(),
+            blocks: (!self.blocks.is_empty()).then(|| self.blocks.clone()),
+            reduced_motion: self.reduced_motion,
+            stats: self.stats.then_some(true),
+            max_bandwidth: self.max_bandwidth,
+            seed: self.seed,
+        }
+    }
+
+    /// Why `--style` can't be used with the rest of the command line, if it
+    /// can't. Only the flag counts: an exported `REVEAL_STYLE` mustn't stop a
+    /// `--sequence` or a show file given on the command line.
+    pub fn style_conflict(&self, matches: &ArgMatches) -> Option<&'static str> {
+        let style_flag = matches.value_source("style") == Some(ValueSource::CommandLine);
+        match &self.command {
+            Some(Command::Play { .. }) if style_flag || self.sequence.is_some() => {
+                Some("--style and --sequence cannot be used with play")
+            }
+            Some(Command::Play { .. } | Command::Screensaver { .. }) => None,
+            _ if style_flag && self.sequence.is_some() => Some("--sequence cannot be used with --style"),
+            _ => None,
+        }
+    }
+}
+
+#[derive(clap::ValueEnum, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
 pub enum AnimationStyle {
     Rainbow,
     Explosion,
@@ -21,3 +268,42 @@ pub enum AnimationStyle {
     MandelbrotMatrix,
     MandelbrotFast,
 }
+
+#[cfg(test)]
+mod tests {
+    use clap::{CommandFactory, FromArgMatches};
+
+    use super::*;
+
+    fn parse(args: &[&str]) -> (Args, ArgMatches) {
+        let matches = Args::command().try_get_matches_from(args).unwrap();
+        (Args::from_arg_matches(&matches).unwrap(), matches)
+    }
+
+    fn conflict(args: &[&str]) -> Option<&'static str> {
+        let (args, matches) = parse(args);
+        args.style_conflict(&matches)
+    }
+
+    #[test]
+    fn a_style_flag_conflicts_with_a_sequence_or_a_show() {
+        assert!(conflict(&["reveal", "--style", "waves", "--sequence", "rainbow"]).is_some());
+        assert!(conflict(&["reveal", "--style", "waves", "play", "show.toml"]).is_some());
+        assert!(conflict(&["reveal", "play", "show.toml", "--sequence", "rainbow"]).is_some());
+        assert_eq!(conflict(&["reveal", "--style", "waves"]), None);
+        assert_eq!(conflict(&["reveal", "--sequence", "rainbow"]), None);
+    }
+
+    #[test]
+    fn a_style_from_the_environment_never_conflicts() {
+        // The only test that sets REVEAL_STYLE, so no other parse sees it.
+        std::env::set_var("REVEAL_STYLE", "waves");
+        let sequence = parse(&["reveal", "--sequence", "rainbow"]);
+        let show = parse(&["reveal", "play", "show.toml"]);
+        std::env::remove_var("REVEAL_STYLE");
+
+        assert_eq!(sequence.0.style, Some(AnimationStyle::Waves));
+        assert_eq!(sequence.0.style_conflict(&sequence.1), None);
+        assert_eq!(show.0.style_conflict(&show.1), None);
+    }
+}
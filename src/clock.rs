@@ -0,0 +1,81 @@
+use std::{
+    sync::mpsc::Sender,
+    thread,
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Local, NaiveTime, Timelike};
+
+use crate::control::Control;
+
+/// Parses a wall-clock time, `HH:MM` or `HH:MM:SS`, as taken by `--until`.
+pub fn parse_time(s: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(s, "%H:%M:%S")
+        .or_else(|_| NaiveTime::parse_from_str(s, "%H:%M"))
+        .map_err(|_| format!("invalid time '{}', expected HH:MM or HH:MM:SS", s))
+}
+
+/// How long until `time` next comes around, today or else tomorrow.
+pub fn until(time: NaiveTime) -> Duration {
+    let now = Local::now().naive_local();
+    let mut target = now.date().and_time(time);
+    if target <= now {
+        target += chrono::Duration::days(1);
+    }
+    (target - now).to_std().unwrap_or_default()
+}
+
+/// Formats time left as `MM:SS`, or `H:MM:SS` from an hour up, counting any
+/// started second as a whole one.
+pub fn format_remaining(remaining: Duration) -> String {
+    let secs = remaining.as_secs() + u64::from(remaining.subsec_nanos() > 0);
+    let (hours, minutes, seconds) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{:02}:{:02}", minutes, seconds)
+    }
+}
+
+/// Sends the time left until `deadline` to the player every second, and
+/// finishes it once the countdown hits zero.
+pub fn count_down(deadline: Instant, controls: Sender<Control>) {
+    thread::spawn(move || loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if controls.send(Control::SetText(format_remaining(remaining))).is_err() {
+            break;
+        }
+        if remaining.is_zero() {
+            let _ = controls.send(Control::Finish);
+            break;
+        }
+        // Wake up right as the readout changes.
+        let into_second = remaining.subsec_nanos();
+        thread::sleep(if into_second > 0 {
+            Duration::from_nanos(into_second.into())
+        } else {
+            Duration::from_secs(1)
+        });
+    });
+}
+
+/// The current time as the clock shows it.
+pub fn current_time() -> String {
+    format_time(Local::now())
+}
+
+fn format_time(time: DateTime<Local>) -> String {
+    time.format("%H:%M:%S").to_string()
+}
+
+/// Sends the current time to the player every second.
+pub fn tick_clock(controls: Sender<Control>) {
+    thread::spawn(move || loop {
+        let now = Local::now();
+        if controls.send(Control::SetText(format_time(now))).is_err() {
+            break;
+        }
+        let into_second = Duration::from_nanos(now.nanosecond().min(999_999_999).into());
+        thread::sleep(Duration::from_secs(1) - into_second);
+    });
+}
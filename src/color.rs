@@ -0,0 +1,244 @@
+use termion::color::Rgb;
+
+/// Parses `#rrggbb` or `#rgb`; the `#` is optional.
+pub fn parse_hex(s: &str) -> Result<Rgb, String> {
+    let s = s.trim();
+    let digits = s.strip_prefix('#').unwrap_or(s);
+    let invalid = || format!("invalid color '{}', expected #rrggbb", s);
+    if !digits.chars().all(|digit| digit.is_ascii_hexdigit()) {
+        return Err(invalid());
+    }
+    let value = |range: std::ops::Range<usize>| u8::from_str_radix(&digits[range], 16).map_err(|_| invalid());
+    match digits.len() {
+        6 => Ok(Rgb(value(0..2)?, value(2..4)?, value(4..6)?)),
+        3 => Ok(Rgb(value(0..1)? * 17, value(1..2)? * 17, value(2..3)? * 17)),
+        _ => Err(invalid()),
+    }
+}
+
+/// Formats as `#rrggbb`.
+pub fn to_hex(color: Rgb) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.0, color.1, color.2)
+}
+
+/// An sRGB channel in linear light, from 0.0 to 1.0.
+pub fn srgb_to_linear(c: u8) -> f32 {
+    let c = c as f32 / 255.0;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// A channel in linear light back to sRGB.
+pub fn linear_to_srgb(c: f32) -> u8 {
+    let c = unit(c);
+    let c = if c <= 0.003_130_8 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    };
+    to_u8(c)
+}
+
+/// The color with hue `h` in turns (wrapping, so -0.25 is 0.75) and
+/// saturation `s` and value `v` from 0.0 to 1.0. Anything out of range or
+/// NaN is clamped rather than panicking.
+pub fn hsv_to_rgb(h: f32, s: f32, v: f32) -> Rgb {
+    let (s, v) = (unit(s), unit(v));
+    let c = v * s;
+    let (r, g, b) = hue_to_rgb(h, c);
+    let m = v - c;
+    Rgb(to_u8(r + m), to_u8(g + m), to_u8(b + m))
+}
+
+/// Hue (in turns), saturation and value of `color`.
+pub fn rgb_to_hsv(color: Rgb) -> (f32, f32, f32) {
+    let (max, min, h) = hue(color);
+    let s = if max > 0.0 { (max - min) / max } else { 0.0 };
+    (h, s, max)
+}
+
+/// Like [`hsv_to_rgb`], with lightness `l` from 0.0 (black) through 0.5
+/// (full color) to 1.0 (white).
+pub fn hsl_to_rgb(h: f32, s: f32, l: f32) -> Rgb {
+    let (s, l) = (unit(s), unit(l));
+    let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    let (r, g, b) = hue_to_rgb(h, c);
+    let m = l - c / 2.0;
+    Rgb(to_u8(r + m), to_u8(g + m), to_u8(b + m))
+}
+
+/// Hue (in turns), saturation and lightness of `color`.
+pub fn rgb_to_hsl(color: Rgb) -> (f32, f32, f32) {
+    let (max, min, h) = hue(color);
+    let l = (max + min) / 2.0;
+    let s = if max == min { 0.0 } else { (max - min) / (1.0 - (2.0 * l - 1.0).abs()) };
+    (h, unit(s), l)
+}
+
+/// The red, green and blue of a fully saturated `hue` with chroma `c`, before
+/// lifting by the lightest channel.
+fn hue_to_rgb(h: f32, c: f32) -> (f32, f32, f32) {
+    let h = if h.is_finite() { h.rem_euclid(1.0) * 6.0 } else { 0.0 };
+    let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+    match h as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    }
+}
+
+/// The largest and smallest channel and the hue of `color`.
+fn hue(color: Rgb) -> (f32, f32, f32) {
+    let (r, g, b) = (color.0 as f32 / 255.0, color.1 as f32 / 255.0, color.2 as f32 / 255.0);
+    let (max, min) = (r.max(g).max(b), r.min(g).min(b));
+    let delta = max - min;
+    let h = if delta == 0.0 {
+        0.0
+    } else if max == r {
+        ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        (b - r) / delta + 2.0
+    } else {
+        (r - g) / delta + 4.0
+    };
+    (max, min, h / 6.0)
+}
+
+/// A color in the OKLab space, where equal steps look equally far apart.
+/// `l` is lightness from 0.0 to 1.0; `a` and `b` run roughly from -0.4 to 0.4.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Oklab {
+    pub l: f32,
+    pub a: f32,
+    pub b: f32,
+}
+
+impl Oklab {
+    pub fn from_rgb(color: Rgb) -> Self {
+        let (r, g, b) = (srgb_to_linear(color.0), srgb_to_linear(color.1), srgb_to_linear(color.2));
+        let l = (0.412_221_46 * r + 0.536_332_55 * g + 0.051_445_995 * b).cbrt();
+        let m = (0.211_903_5 * r + 0.680_699_5 * g + 0.107_396_96 * b).cbrt();
+        let s = (0.088_302_46 * r + 0.281_718_85 * g + 0.629_978_7 * b).cbrt();
+        Self {
+            l: 0.210_454_26 * l + 0.793_617_8 * m - 0.004_072_047 * s,
+            a: 1.977_998_5 * l - 2.428_592_2 * m + 0.450_593_7 * s,
+            b: 0.025_904_037 * l + 0.782_771_77 * m - 0.808_675_77 * s,
+        }
+    }
+
+    /// The nearest 24-bit color; colors outside sRGB are clipped.
+    pub fn to_rgb(self) -> Rgb {
+        let l = (self.l + 0.396_337_78 * self.a + 0.215_803_76 * self.b).powi(3);
+        let m = (self.l - 0.105_561_346 * self.a - 0.063_854_17 * self.b).powi(3);
+        let s = (self.l - 0.089_484_18 * self.a - 1.291_485_5 * self.b).powi(3);
+        Rgb(
+            linear_to_srgb(4.076_741_7 * l - 3.307_711_6 * m + 0.230_969_94 * s),
+            linear_to_srgb(-1.268_438 * l + 2.609_757_4 * m - 0.341_319_38 * s),
+            linear_to_srgb(-0.004_196_086_3 * l - 0.703_418_6 * m + 1.707_614_7 * s),
+        )
+    }
+}
+
+/// OKLab in polar form: lightness, chroma and hue (in turns).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Oklch {
+    pub l: f32,
+    pub c: f32,
+    pub h: f32,
+}
+
+impl Oklch {
+    pub fn from_rgb(color: Rgb) -> Self {
+        Self::from_oklab(Oklab::from_rgb(color))
+    }
+
+    pub fn to_rgb(self) -> Rgb {
+        self.to_oklab().to_rgb()
+    }
+
+    pub fn from_oklab(lab: Oklab) -> Self {
+        let h = lab.b.atan2(lab.a) / std::f32::consts::TAU;
+        Self {
+            l: lab.l,
+            c: lab.a.hypot(lab.b),
+            h: h.rem_euclid(1.0),
+        }
+    }
+
+    pub fn to_oklab(self) -> Oklab {
+        let angle = self.h * std::f32::consts::TAU;
+        Oklab {
+            l: self.l,
+            a: self.c * angle.cos(),
+            b: self.c * angle.sin(),
+        }
+    }
+}
+
+/// Blends `from` into `to` by `t` (0.0 to 1.0) in linear light, so the middle
+/// isn't muddier or darker than either end, as it is when mixing sRGB values.
+pub fn mix(from: Rgb, to: Rgb, t: f32) -> Rgb {
+    let t = unit(t);
+    let channel = |a: u8, b: u8| {
+        let (a, b) = (srgb_to_linear(a), srgb_to_linear(b));
+        linear_to_srgb(a + (b - a) * t)
+    };
+    Rgb(channel(from.0, to.0), channel(from.1, to.1), channel(from.2, to.2))
+}
+
+/// Blends `from` into `to` by `t` (0.0 to 1.0) in OKLab, for gradients whose
+/// steps look even.
+pub fn mix_oklab(from: Rgb, to: Rgb, t: f32) -> Rgb {
+    let t = unit(t);
+    if t == 0.0 {
+        return from;
+    }
+    if t == 1.0 {
+        return to;
+    }
+    let (a, b) = (Oklab::from_rgb(from), Oklab::from_rgb(to));
+    Oklab {
+        l: a.l + (b.l - a.l) * t,
+        a: a.a + (b.a - a.a) * t,
+        b: a.b + (b.b - a.b) * t,
+    }
+    .to_rgb()
+}
+
+/// Relative luminance as WCAG defines it, from 0.0 (black) to 1.0 (white).
+pub fn relative_luminance(color: Rgb) -> f32 {
+    0.2126 * srgb_to_linear(color.0) + 0.7152 * srgb_to_linear(color.1) + 0.0722 * srgb_to_linear(color.2)
+}
+
+/// The WCAG contrast ratio between two colors, from 1.0 (none) to 21.0
+/// (black on white). Body text wants 4.5 or more.
+pub fn contrast_ratio(a: Rgb, b: Rgb) -> f32 {
+    let (a, b) = (relative_luminance(a), relative_luminance(b));
+    (a.max(b) + 0.05) / (a.min(b) + 0.05)
+}
+
+/// `color` scaled towards black by `value`, from 0.0 (black) to 1.0 (as is).
+pub fn shade(color: Rgb, value: f32) -> Rgb {
+    let value = unit(value);
+    let scale = |c: u8| (c as f32 * value).round() as u8;
+    Rgb(scale(color.0), scale(color.1), scale(color.2))
+}
+
+/// Clamps to 0.0..=1.0, taking NaN as 0.0.
+fn unit(x: f32) -> f32 {
+    if x.is_nan() {
+        0.0
+    } else {
+        x.clamp(0.0, 1.0)
+    }
+}
+
+fn to_u8(c: f32) -> u8 {
+    (unit(c) * 255.0).round() as u8
+}
@@ -0,0 +1,252 @@
+use std::{
+    collections::BTreeMap,
+    env,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+use crate::bandwidth;
+use crate::cli::AnimationStyle;
+use crate::layout::{Align, Margins, TextBlock, VAlign};
+use crate::legibility::{TextColor, TextEffect};
+use crate::loader::{self, LoadError};
+use crate::motion::ReducedMotion;
+use crate::palette::Palette;
+use crate::sequence::SceneDefaults;
+use crate::terminal::Rect;
+use crate::transition::TransitionKind;
+
+/// The frame rates `--fps` accepts.
+pub const FPS_RANGE: std::ops::RangeInclusive<u32> = 1..=240;
+
+/// Everything that can be set from flags, environment variables, a preset or
+/// the config file. Unset fields fall through to the next layer.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Settings {
+    pub style: Option<AnimationStyle>,
+    pub text: Option<String>,
+    pub duration: Option<u64>,
+    pub loops: Option<u32>,
+    pub transition: Option<TransitionKind>,
+    pub transition_duration: Option<u64>,
+    pub fps: Option<u32>,
+    /// A built-in palette's name or gradient stops, as `--palette` or
+    /// `--gradient` take them.
+    pub palette: Option<Palette>,
+    pub theme: Option<PathBuf>,
+    pub text_color: Option<TextColor>,
+    pub text_effect: Option<TextEffect>,
+    pub align: Option<Align>,
+    pub valign: Option<VAlign>,
+    /// `[x, y]` in a file.
+    pub offset: Option<(i16, i16)>,
+    pub margin: Option<Margins>,
+    /// Draw the text in large block glyphs.
+    pub big_text: Option<bool>,
+    /// Lines to animate in below the cursor instead of full screen.
+    pub inline: Option<u16>,
+    /// `[x, y, width, height]` in a file.
+    pub viewport: Option<Rect>,
+    pub border: Option<bool>,
+    /// Set into the border; implies `border`.
+    pub title: Option<String>,
+    /// Extra text, each written as for `--block`.
+    pub blocks: Option<Vec<TextBlock>>,
+    pub reduced_motion: Option<ReducedMotion>,
+    /// Overlay and print frame statistics.
+    pub stats: Option<bool>,
+    /// Bytes per second, e.g. `20000` or `"20k"`.
+    #[serde(default, deserialize_with = "bandwidth::deserialize_rate")]
+    pub max_bandwidth: Option<u64>,
+    pub seed: Option<u64>,
+}
+
+impl Settings {
+    /// Fills every field left unset here from `fallback`.
+    pub fn or(self, fallback: Settings) -> Settings {
+        Settings {
+            style: self.style.or(fallback.style),
+            text: self.text.or(fallback.text),
+            duration: self.duration.or(fallback.duration),
+            loops: self.loops.or(fallback.loops),
+            transition: self.transition.or(fallback.transition),
+            transition_duration: self.transition_duration.or(fallback.transition_duration),
+            fps: self.fps.or(fallback.fps),
+            palette: self.palette.or(fallback.palette),
+            theme: self.theme.or(fallback.theme),
+            text_color: self.text_color.or(fallback.text_color),
+            text_effect: self.text_effect.or(fallback.text_effect),
+            align: self.align.or(fallback.align),
+            valign: self.valign.or(fallback.valign),
+            offset: self.offset.or(fallback.offset),
+            margin: self.margin.or(fallback.margin),
+            big_text: self.big_text.or(fallback.big_text),
+            inline: self.inline.or(fallback.inline),
+            viewport: self.viewport.or(fallback.viewport),
+            border: self.border.or(fallback.border),
+            title: self.title.or(fallback.title),
+            blocks: self.blocks.or(fallback.blocks),
+            reduced_motion: self.reduced_motion.or(fallback.reduced_motion),
+            stats: self.stats.or(fallback.stats),
+            max_bandwidth: self.max_bandwidth.or(fallback.max_bandwidth),
+            seed: self.seed.or(fallback.seed),
+        }
+    }
+
+    /// Checks what the file's types can't, and makes a relative theme path
+    /// relative to `dir`, the directory of the file it was read from. `field`
+    /// is where these settings sit in the file, for error messages.
+    pub fn resolve(&mut self, dir: &Path, field: &str) -> Result<(), LoadError> {
+        if let Some(fps) = self.fps.filter(|fps| !FPS_RANGE.contains(fps)) {
+            return Err(LoadError::Invalid {
+                field: format!("{}fps", field),
+                message: format!("{} is not in {}..={}", fps, FPS_RANGE.start(), FPS_RANGE.end()),
+            });
+        }
+        if let Some(theme) = &mut self.theme {
+            *theme = dir.join(&*theme);
+        }
+        Ok(())
+    }
+
+    /// The scene fields. `big_text` stays show-wide, so that commands like
+    /// `countdown` can turn it on for every scene.
+    pub fn scene_defaults(&self) -> SceneDefaults {
+        SceneDefaults {
+            text: self.text.clone(),
+            duration: self.duration,
+            transition: self.transition,
+            transition_duration: self.transition_duration,
+            palette: self.palette.clone(),
+            text_color: self.text_color,
+            text_effect: self.text_effect,
+            big_text: None,
+        }
+    }
+}
+
+/// The user config file, `~/.config/reveal/config.toml` by default.
+///
+/// ```toml
+/// [defaults]
+/// style = "waves"
+/// duration = 3000
+///
+/// [presets.launch]
+/// style = "explosion"
+/// text = "We have liftoff"
+/// palette = "sunset"
+/// text-effect = "outline"
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    #[serde(default)]
+    pub defaults: Settings,
+    #[serde(default)]
+    pub presets: BTreeMap<String, Settings>,
+}
+
+impl Config {
+    /// `$XDG_CONFIG_HOME/reveal/config.toml`, falling back to `~/.config`.
+    pub fn default_path() -> Option<PathBuf> {
+        let base = env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        Some(base.join("reveal").join("config.toml"))
+    }
+
+    pub fn load(path: &Path) -> Result<Config, LoadError> {
+        let mut config: Config = loader::load(path)?;
+        let dir = path.parent().unwrap_or(Path::new(""));
+        config.defaults.resolve(dir, "defaults.")?;
+        for (name, preset) in &mut config.presets {
+            preset.resolve(dir, &format!("presets.{}.", name))?;
+        }
+        Ok(config)
+    }
+
+    /// The settings below the command line: the named preset on top of the
+    /// config file's defaults.
+    pub fn settings(&self, preset: Option<&str>) -> Result<Settings, LoadError> {
+        let Some(name) = preset else {
+            return Ok(self.defaults.clone());
+        };
+        match self.presets.get(name) {
+            Some(preset) => Ok(preset.clone().or(self.defaults.clone())),
+            None => {
+                let available: Vec<&str> = self.presets.keys().map(String::as_str).collect();
+                Err(LoadError::Invalid {
+                    field: format!("presets.{}", name),
+                    message: if available.is_empty() {
+                        "no presets are defined".to_string()
+                    } else {
+                        format!("unknown preset (available: {})", available.join(", "))
+                    },
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+[defaults]
+style = "waves"
+duration = 3000
+fps = 30
+
+[presets.launch]
+style = "explosion"
+text = "We have liftoff"
+
+[presets.calm]
+reduced-motion = "static"
+"#;
+
+    fn config() -> Config {
+        loader::from_toml(CONFIG).unwrap()
+    }
+
+    #[test]
+    fn presets_sit_on_top_of_the_defaults() {
+        let settings = config().settings(Some("launch")).unwrap();
+        assert_eq!(settings.style, Some(AnimationStyle::Explosion));
+        assert_eq!(settings.text.as_deref(), Some("We have liftoff"));
+        assert_eq!(settings.duration, Some(3000));
+        assert_eq!(config().settings(None).unwrap().style, Some(AnimationStyle::Waves));
+    }
+
+    #[test]
+    fn each_layer_only_fills_what_the_ones_above_leave_unset() {
+        let flags = Settings { duration: Some(100), ..Settings::default() };
+        let show = Settings { duration: Some(200), loops: Some(2), fps: Some(60), ..Settings::default() };
+        let settings = flags.or(show).or(config().settings(Some("launch")).unwrap());
+        assert_eq!(settings.duration, Some(100));
+        assert_eq!(settings.loops, Some(2));
+        assert_eq!(settings.fps, Some(60));
+        assert_eq!(settings.style, Some(AnimationStyle::Explosion));
+        assert_eq!(settings.title, None);
+    }
+
+    #[test]
+    fn unknown_presets_list_the_defined_ones() {
+        match config().settings(Some("nope")) {
+            Err(LoadError::Invalid { field, message }) => {
+                assert_eq!(field, "presets.nope");
+                assert_eq!(message, "unknown preset (available: calm, launch)");
+            }
+            other => panic!("expected an unknown preset, got {:?}", other),
+        }
+        match Config::default().settings(Some("launch")) {
+            Err(LoadError::Invalid { message, .. }) => assert_eq!(message, "no presets are defined"),
+            other => panic!("expected an unknown preset, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,149 @@
+use std::{
+    fs::File,
+    io::{self, Read},
+    os::unix::io::AsRawFd,
+    sync::atomic::{AtomicBool, Ordering},
+    sync::mpsc::{self, Receiver, Sender},
+    sync::Arc,
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use crate::cli::AnimationStyle;
+use crate::player::{play, PlayOptions, Scene};
+use crate::transition::{Transition, TransitionKind};
+
+/// Commands a running player accepts while it plays.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Control {
+    /// Replace the text of every scene.
+    SetText(String),
+    /// Show progress from 0.0 to 1.0.
+    SetProgress(f32),
+    /// Switch the current scene to another style.
+    SetStyle(AnimationStyle),
+    /// Play a style once over the show, e.g. an explosion on demand.
+    Trigger(AnimationStyle),
+    Pause,
+    Resume,
+    /// The terminal is now this many columns and rows.
+    Resize(u16, u16),
+    /// Play the outro, then stop.
+    Finish,
+    /// Stop right away.
+    Cancel,
+}
+
+/// A player running on a background thread, e.g. as a splash screen while a
+/// CLI tool does its work. Dropping the handle cancels the animation.
+pub struct RevealHandle {
+    controls: Sender<Control>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl RevealHandle {
+    pub fn update_text(&self, text: &str) {
+        let _ = self.controls.send(Control::SetText(text.to_string()));
+    }
+
+    /// Sets the progress shown along the bottom row, from 0.0 to 1.0.
+    pub fn set_progress(&self, progress: f32) {
+        let _ = self.controls.send(Control::SetProgress(progress));
+    }
+
+    /// Plays the outro and waits until the terminal is restored.
+    pub fn finish(mut self) {
+        self.stop(Control::Finish);
+    }
+
+    /// Stops immediately and waits until the terminal is restored.
+    pub fn cancel(mut self) {
+        self.stop(Control::Cancel);
+    }
+
+    fn stop(&mut self, control: Control) {
+        let _ = self.controls.send(control);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for RevealHandle {
+    fn drop(&mut self) {
+        self.stop(Control::Cancel);
+    }
+}
+
+/// The outro `finish()` plays when the options don't name one.
+pub fn default_outro() -> Scene {
+    Scene {
+        transition: Some(Transition {
+            kind: TransitionKind::Crossfade,
+            duration: Duration::from_millis(300),
+        }),
+        ..Scene::new(AnimationStyle::Explosion, "", Duration::from_millis(1000))
+    }
+}
+
+/// Starts playing `scenes` on a background thread. Ctrl-C stops it and,
+/// once the terminal is restored, interrupts the host as it normally would.
+/// Fails when stdout is not a terminal.
+pub fn spawn(scenes: Vec<Scene>, options: PlayOptions) -> io::Result<RevealHandle> {
+    start(scenes, options, true)
+}
+
+/// Like [`spawn`], but with `watch_keys` off the caller reads the keys and
+/// has to cancel on Ctrl-C itself.
+pub(crate) fn start(scenes: Vec<Scene>, mut options: PlayOptions, watch_keys: bool) -> io::Result<RevealHandle> {
+    if !termion::is_tty(&io::stdout()) {
+        return Err(io::Error::other("stdout is not a terminal"));
+    }
+    termion::terminal_size()?;
+    let (controls, receiver): (Sender<Control>, Receiver<Control>) = mpsc::channel();
+    options.outro.get_or_insert_with(default_outro);
+
+    let playing = Arc::new(AtomicBool::new(true));
+    let interrupted = Arc::new(AtomicBool::new(false));
+    if watch_keys {
+        watch_ctrl_c(termion::get_tty()?, controls.clone(), playing.clone(), interrupted.clone());
+    }
+    let thread = thread::spawn(move || {
+        let _ = play(&scenes, &options, &receiver);
+        playing.store(false, Ordering::Relaxed);
+        if interrupted.load(Ordering::Relaxed) {
+            // SAFETY: raising a signal has no memory safety requirements.
+            unsafe { libc::raise(libc::SIGINT) };
+        }
+    });
+    Ok(RevealHandle {
+        controls,
+        thread: Some(thread),
+    })
+}
+
+/// Cancels the player when Ctrl-C comes in on `tty`. Raw mode turns Ctrl-C
+/// into a plain byte instead of a signal, so the player has to look for it.
+/// Polls rather than blocks so that it stops with the player and leaves
+/// later keys to the host.
+fn watch_ctrl_c(mut tty: File, controls: Sender<Control>, playing: Arc<AtomicBool>, interrupted: Arc<AtomicBool>) {
+    thread::spawn(move || {
+        let mut poll = libc::pollfd { fd: tty.as_raw_fd(), events: libc::POLLIN, revents: 0 };
+        let mut bytes = [0; 64];
+        while playing.load(Ordering::Relaxed) {
+            // SAFETY: `poll` is a single valid pollfd for the whole call.
+            if unsafe { libc::poll(&mut poll, 1, 50) } <= 0 {
+                continue;
+            }
+            match tty.read(&mut bytes) {
+                Ok(0) | Err(_) => return,
+                Ok(read) if bytes[..read].contains(&3) => {
+                    interrupted.store(true, Ordering::Relaxed);
+                    let _ = controls.send(Control::Cancel);
+                    return;
+                }
+                Ok(_) => {}
+            }
+        }
+    });
+}
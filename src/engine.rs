@@ -0,0 +1,632 @@
+use std::time::Duration;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use termion::color::Rgb;
+
+use crate::animation::Animation;
+use crate::buffer::Buffer;
+use crate::cli::AnimationStyle;
+use crate::font::big_text_glyphs;
+use crate::color::shade;
+use crate::helper::{dim, draw_progress_bar, fill_progress};
+use crate::layout::{Placement, TextBlock};
+use crate::legibility::{TextColor, TextEffect, TextStyle};
+use crate::motion::{Limiter, ReducedMotion, StaticGradient};
+use crate::palette::Palette;
+use crate::player::Scene;
+use crate::terminal::Cell;
+use crate::theme::Theme;
+use crate::transition::Transition;
+
+/// A show that the caller drives frame by frame, for embedding reveal
+/// effects in other applications. It performs no I/O: advance it with
+/// [`Reveal::tick`] and read the frame back with [`Reveal::cells`].
+///
+/// ```
+/// use std::time::Duration;
+/// use reveal::{AnimationStyle, Reveal};
+///
+/// let mut reveal = Reveal::builder()
+///     .style(AnimationStyle::Waves)
+///     .text("Hello")
+///     .size(40, 10)
+///     .build();
+///
+/// // In the host application's own loop:
+/// reveal.tick(Duration::from_millis(16));
+/// for (i, cell) in reveal.cells().iter().enumerate() {
+///     let (x, y) = (i % 40, i / 40);
+///     // draw `cell.char` in `cell.color` at (x, y)
+/// }
+/// ```
+pub struct Reveal {
+    scenes: Vec<Scene>,
+    loops: u32,
+    text: Option<String>,
+    frame: Buffer,
+    outgoing: Buffer,
+    incoming: Buffer,
+    current: Running,
+    /// The scene being transitioned away from, while a transition runs.
+    previous: Option<Running>,
+    /// How many scenes have started, counting every loop.
+    position: usize,
+    progress: Option<f32>,
+    progress_style: ProgressStyle,
+    /// Draw the text in large block glyphs over the animation.
+    big_text: bool,
+    /// Pick each next scene at random instead of in order.
+    shuffle: bool,
+    /// Fade down to a low brightness over this long, against burn-in.
+    dim: Option<Duration>,
+    /// Time played in total, across every scene.
+    clock: Duration,
+    /// A one-off animation shown over the show, e.g. a triggered explosion.
+    interlude: Option<Interlude>,
+    paused: bool,
+    motion: ReducedMotion,
+    /// Colors every style draws with instead of its own.
+    palette: Option<Palette>,
+    theme: Theme,
+    text_style: TextStyle,
+    /// Where the scenes' text goes.
+    placement: Placement,
+    /// Text placed on its own, the same in every scene.
+    blocks: Vec<TextBlock>,
+    /// Detail the animations draw at, from 0.0 to 1.0.
+    quality: f32,
+    /// Holds back brightness changes that are too fast with reduced motion.
+    limiter: Option<Limiter>,
+    /// Set once the outro started; the show ends with the current scene.
+    ending: bool,
+    finished: bool,
+    seed: u64,
+    /// All randomness in the show comes from here, so a seed replays it.
+    rng: StdRng,
+}
+
+/// The brightness a dimmed picture settles at.
+const MIN_BRIGHTNESS: f32 = 0.25;
+
+/// Text and progress bar color when the theme doesn't set one.
+const TEXT_COLOR: Rgb = Rgb(255, 255, 255);
+
+/// How progress set with [`Reveal::set_progress`] is shown.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ProgressStyle {
+    /// A bar along the bottom row.
+    #[default]
+    Bar,
+    /// The animation itself fills the screen from the left, with the text
+    /// drawn on top. The outro always plays full screen.
+    Fill,
+}
+
+/// A scene's animation and how long it has been playing.
+struct Running {
+    scene: usize,
+    animation: Box<dyn Animation>,
+    elapsed: Duration,
+    motion: ReducedMotion,
+}
+
+impl Running {
+    fn start(
+        scenes: &[Scene],
+        scene: usize,
+        elapsed: Duration,
+        motion: ReducedMotion,
+        palette: Option<&Palette>,
+        rng: &mut StdRng,
+    ) -> Self {
+        // A scene's own palette wins over the show's.
+        let palette = scenes[scene].palette.as_ref().or(palette);
+        Self {
+            scene,
+            animation: animation(&scenes[scene].style, motion, palette, rng),
+            elapsed,
+            motion,
+        }
+    }
+
+    /// Draws the animation alone; the engine lays the text out on top.
+    fn render(&mut self, buffer: &mut Buffer) {
+        self.animation.render(buffer, animation_time(self.elapsed, self.motion));
+    }
+}
+
+/// The animation for `style` in `palette`, or the still stand-in when
+/// motion is off.
+fn animation(
+    style: &AnimationStyle,
+    motion: ReducedMotion,
+    palette: Option<&Palette>,
+    rng: &mut StdRng,
+) -> Box<dyn Animation> {
+    let mut animation = match motion {
+        ReducedMotion::Static => Box::new(StaticGradient::default()),
+        _ => style.animation(rng.gen()),
+    };
+    if let Some(palette) = palette {
+        animation.set_palette(palette);
+    }
+    animation
+}
+
+/// How far into its animation a style is after `elapsed`.
+fn animation_time(elapsed: Duration, motion: ReducedMotion) -> Duration {
+    match motion {
+        ReducedMotion::Limit => elapsed / ReducedMotion::SLOWDOWN,
+        _ => elapsed,
+    }
+}
+
+/// An animation played once on top of the show, which carries on underneath.
+struct Interlude {
+    animation: Box<dyn Animation>,
+    elapsed: Duration,
+    duration: Duration,
+}
+
+impl Reveal {
+    pub fn builder() -> RevealBuilder {
+        RevealBuilder::default()
+    }
+
+    /// Advances the show by `dt` and renders the resulting frame.
+    pub fn tick(&mut self, dt: Duration) {
+        if self.finished {
+            return;
+        }
+        // A paused show still renders, so new text shows up. The limiter
+        // goes by the wall clock, so that text fades in even while paused.
+        let frame_dt = dt;
+        let dt = if self.paused { Duration::ZERO } else { dt };
+        self.current.elapsed += dt;
+        self.clock += dt;
+        if let Some(previous) = &mut self.previous {
+            previous.elapsed += dt;
+        }
+
+        // Move on past every scene that has run its course, carrying the
+        // overshoot into the next one so timing doesn't drift.
+        loop {
+            let duration = self.scenes[self.current.scene].duration;
+            if duration.is_zero() || self.current.elapsed < duration {
+                break;
+            }
+            self.position += 1;
+            if self.ending || (self.loops != 0 && self.position >= self.scenes.len() * self.loops as usize) {
+                self.finished = true;
+                return;
+            }
+            let scene = self.next_scene();
+            let elapsed = self.current.elapsed - duration;
+            let next = Running::start(&self.scenes, scene, elapsed, self.motion, self.palette.as_ref(), &mut self.rng);
+            self.previous = Some(std::mem::replace(&mut self.current, next));
+        }
+        if let Some(interlude) = &mut self.interlude {
+            interlude.elapsed += dt;
+            if interlude.elapsed >= interlude.duration {
+                self.interlude = None;
+            }
+        }
+
+        self.current.animation.set_quality(self.quality);
+        if let Some(previous) = &mut self.previous {
+            previous.animation.set_quality(self.quality);
+        }
+        if let Some(interlude) = &mut self.interlude {
+            interlude.animation.set_quality(self.quality);
+        }
+
+        let fill = self.progress.filter(|_| self.progress_style == ProgressStyle::Fill && !self.ending);
+        let transition = self.transition();
+        match (&mut self.previous, transition) {
+            _ if self.interlude.is_some() => {
+                if let Some(interlude) = &mut self.interlude {
+                    interlude.animation.render(&mut self.frame, animation_time(interlude.elapsed, self.motion));
+                }
+            }
+            (Some(previous), Some(transition)) => {
+                previous.render(&mut self.outgoing);
+                self.current.render(&mut self.incoming);
+                let progress = self.current.elapsed.as_secs_f32() / transition.duration.as_secs_f32();
+                transition.composite(&self.outgoing, &self.incoming, progress, &mut self.frame);
+            }
+            _ => {
+                self.previous = None;
+                self.current.render(&mut self.frame);
+            }
+        }
+
+        match (self.progress, fill) {
+            (_, Some(progress)) => fill_progress(&mut self.frame, progress),
+            (Some(progress), None) if self.progress_style == ProgressStyle::Bar => {
+                draw_progress_bar(&mut self.frame, progress, self.theme.accent.unwrap_or(TEXT_COLOR));
+            }
+            _ => {}
+        }
+        self.draw_text();
+
+        if let Some(period) = self.dim.filter(|period| !period.is_zero()) {
+            let dimmed = (self.clock.as_secs_f32() / period.as_secs_f32()).min(1.0);
+            dim(&mut self.frame, 1.0 - dimmed * (1.0 - MIN_BRIGHTNESS));
+        }
+        if let Some(limiter) = &mut self.limiter {
+            limiter.apply(&mut self.frame, frame_dt);
+        }
+    }
+
+    fn next_scene(&mut self) -> usize {
+        let count = self.scenes.len();
+        if !self.shuffle || count < 2 {
+            return self.position % count;
+        }
+        // Never repeat the scene that just played.
+        let skip = self.rng.gen_range(1..count);
+        (self.current.scene + skip) % count
+    }
+
+    /// Lays the scene's text and any extra blocks out over the frame.
+    fn draw_text(&mut self) {
+        let area = (self.frame.width, self.frame.height);
+        let scene = &self.scenes[self.current.scene];
+        let mut style = self.text_style;
+        style.color = scene.text_color.unwrap_or(style.color);
+        style.effect = scene.text_effect.unwrap_or(style.effect);
+        if self.motion == ReducedMotion::Static {
+            if let TextColor::Fixed(color) = style.color {
+                let brightness = StaticGradient::text_brightness(animation_time(self.current.elapsed, self.motion));
+                style.color = TextColor::Fixed(shade(color, brightness));
+            }
+        }
+
+        let text = self.text.as_deref().unwrap_or(&scene.text);
+        let big_text = scene.big_text.unwrap_or(self.big_text);
+        match big_text.then(|| big_text_glyphs(&self.frame, text, &self.placement)).flatten() {
+            Some(glyphs) => {
+                let color = self.theme.accent.map_or(style.color, TextColor::Fixed);
+                TextStyle { color, ..style }.draw(&mut self.frame, &glyphs);
+            }
+            None => style.draw(&mut self.frame, &self.placement.glyphs(area, text)),
+        }
+        for block in &self.blocks {
+            style.draw(&mut self.frame, &block.placement(self.placement.margins).glyphs(area, &block.text));
+        }
+    }
+
+    /// The transition into the current scene, while it is still running.
+    fn transition(&self) -> Option<Transition> {
+        self.scenes[self.current.scene]
+            .transition
+            .filter(|transition| self.current.elapsed < transition.duration)
+    }
+
+    /// The last rendered frame, row by row.
+    pub fn cells(&self) -> &[Cell] {
+        self.frame.cells()
+    }
+
+    pub fn buffer(&self) -> &Buffer {
+        &self.frame
+    }
+
+    pub fn width(&self) -> u16 {
+        self.frame.width
+    }
+
+    pub fn height(&self) -> u16 {
+        self.frame.height
+    }
+
+    /// The scene currently playing.
+    pub fn scene(&self) -> &Scene {
+        &self.scenes[self.current.scene]
+    }
+
+    /// Replaces the text of every scene, or goes back to each scene's own
+    /// text with `None`.
+    pub fn set_text(&mut self, text: Option<&str>) {
+        self.text = text.map(str::to_string);
+    }
+
+    /// Shows progress from 0.0 to 1.0 in the builder's [`ProgressStyle`], or
+    /// hides it with `None`.
+    pub fn set_progress(&mut self, progress: Option<f32>) {
+        self.progress = progress.map(|progress| progress.clamp(0.0, 1.0));
+    }
+
+    /// Switches the current scene to `style`, keeping its text and timing.
+    pub fn set_style(&mut self, style: AnimationStyle) {
+        let palette = self.scenes[self.current.scene].palette.as_ref().or(self.palette.as_ref());
+        self.current.animation = animation(&style, self.motion, palette, &mut self.rng);
+        self.scenes[self.current.scene].style = style;
+    }
+
+    /// Plays `style` once over the show for `duration`, then carries on
+    /// with the show, which keeps running underneath.
+    pub fn trigger(&mut self, style: AnimationStyle, duration: Duration) {
+        self.interlude = Some(Interlude {
+            animation: animation(&style, self.motion, self.palette.as_ref(), &mut self.rng),
+            elapsed: Duration::ZERO,
+            duration,
+        });
+    }
+
+    /// Sets how much detail the animations draw, from 0.0 (cheapest) to 1.0
+    /// (full), to keep up a frame rate on slow machines.
+    pub fn set_quality(&mut self, quality: f32) {
+        self.quality = quality.clamp(0.0, 1.0);
+    }
+
+    /// Freezes (or unfreezes) time; the frame keeps rendering.
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Cuts over to `scene` (using its transition) and ends the show once
+    /// it has played. An outro without text keeps the text on screen.
+    pub fn outro(&mut self, mut scene: Scene) {
+        if scene.text.is_empty() && self.text.is_none() {
+            scene.text = self.scene().text.clone();
+        }
+        self.scenes.push(scene);
+        let scene = self.scenes.len() - 1;
+        let next = Running::start(&self.scenes, scene, Duration::ZERO, self.motion, self.palette.as_ref(), &mut self.rng);
+        self.previous = Some(std::mem::replace(&mut self.current, next));
+        self.ending = true;
+    }
+
+    /// The seed all of the show's randomness comes from.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Changes the frame size; takes effect from the next tick.
+    pub fn resize(&mut self, width: u16, height: u16) {
+        self.frame = Buffer::new(width, height);
+        self.outgoing = self.frame.clone();
+        self.incoming = self.frame.clone();
+    }
+
+    /// Whether every scene (and loop) has played out.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// How long to wait before the next tick for smooth motion.
+    pub fn frame_interval(&self) -> Duration {
+        let interval = self.current.animation.frame_interval();
+        match &self.previous {
+            Some(previous) => interval.min(previous.animation.frame_interval()),
+            None => interval,
+        }
+    }
+}
+
+/// Configures a [`Reveal`]. Either give a single `style`/`text`/`duration`,
+/// or a list of scenes.
+pub struct RevealBuilder {
+    style: AnimationStyle,
+    text: String,
+    duration: Duration,
+    scenes: Vec<Scene>,
+    loops: u32,
+    size: (u16, u16),
+    progress_style: ProgressStyle,
+    big_text: bool,
+    shuffle: bool,
+    dim: Option<Duration>,
+    reduced_motion: ReducedMotion,
+    palette: Option<Palette>,
+    theme: Theme,
+    text_color: Option<TextColor>,
+    text_effect: Option<TextEffect>,
+    placement: Placement,
+    blocks: Vec<TextBlock>,
+    seed: Option<u64>,
+}
+
+impl Default for RevealBuilder {
+    fn default() -> Self {
+        Self {
+            style: AnimationStyle::Rainbow,
+            text: String::new(),
+            duration: Duration::ZERO,
+            scenes: Vec::new(),
+            loops: 1,
+            size: (80, 24),
+            progress_style: ProgressStyle::Bar,
+            big_text: false,
+            shuffle: false,
+            dim: None,
+            reduced_motion: ReducedMotion::Off,
+            palette: None,
+            theme: Theme::default(),
+            text_color: None,
+            text_effect: None,
+            placement: Placement::default(),
+            blocks: Vec::new(),
+            seed: None,
+        }
+    }
+}
+
+impl RevealBuilder {
+    pub fn style(mut self, style: AnimationStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn text(mut self, text: &str) -> Self {
+        self.text = text.to_string();
+        self
+    }
+
+    /// How long the single style plays. Zero, the default, plays forever.
+    pub fn duration(mut self, duration: Duration) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    /// Adds a scene; once any are added, `style`, `text` and `duration` are ignored.
+    pub fn scene(mut self, scene: Scene) -> Self {
+        self.scenes.push(scene);
+        self
+    }
+
+    pub fn scenes(mut self, scenes: impl IntoIterator<Item = Scene>) -> Self {
+        self.scenes.extend(scenes);
+        self
+    }
+
+    /// Times to play the scenes (forever if 0).
+    pub fn loops(mut self, loops: u32) -> Self {
+        self.loops = loops;
+        self
+    }
+
+    /// Frame size in cells.
+    pub fn size(mut self, width: u16, height: u16) -> Self {
+        self.size = (width, height);
+        self
+    }
+
+    pub fn progress_style(mut self, style: ProgressStyle) -> Self {
+        self.progress_style = style;
+        self
+    }
+
+    /// Draws the text in large block glyphs over the animation, for clock
+    /// readouts. Only digits, `:` and spaces have big glyphs.
+    pub fn big_text(mut self, big_text: bool) -> Self {
+        self.big_text = big_text;
+        self
+    }
+
+    /// Plays the scenes in random order, starting anywhere and never the
+    /// same one twice in a row.
+    pub fn shuffle(mut self, shuffle: bool) -> Self {
+        self.shuffle = shuffle;
+        self
+    }
+
+    /// Fades the picture down to a low brightness over `period`, to keep
+    /// long-running displays from burning in.
+    pub fn dim(mut self, period: Option<Duration>) -> Self {
+        self.dim = period;
+        self
+    }
+
+    /// Calms the animation for viewers sensitive to motion or flashes.
+    pub fn reduced_motion(mut self, motion: ReducedMotion) -> Self {
+        self.reduced_motion = motion;
+        self
+    }
+
+    /// Draws every style with `palette` instead of its own colors.
+    pub fn palette(mut self, palette: Palette) -> Self {
+        self.palette = Some(palette);
+        self
+    }
+
+    /// Draws with the theme's colors: its palette (unless one is set with
+    /// [`RevealBuilder::palette`]), text colors and accent.
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Draws the text in `color`, or picks black or white per character with
+    /// [`TextColor::Auto`]. Overrides the theme's text color.
+    pub fn text_color(mut self, color: TextColor) -> Self {
+        self.text_color = Some(color);
+        self
+    }
+
+    /// Sets the text off from the animation with an outline, shadow, box or
+    /// glow. A theme with a text background gets a box unless this says
+    /// otherwise.
+    pub fn text_effect(mut self, effect: TextEffect) -> Self {
+        self.text_effect = Some(effect);
+        self
+    }
+
+    /// Places the scenes' text somewhere other than the middle.
+    pub fn placement(mut self, placement: Placement) -> Self {
+        self.placement = placement;
+        self
+    }
+
+    /// Adds text placed on its own, e.g. a subtitle along the bottom, drawn
+    /// in every scene.
+    pub fn block(mut self, block: TextBlock) -> Self {
+        self.blocks.push(block);
+        self
+    }
+
+    /// Seeds every random choice, so the same seed, size and ticks give the
+    /// same frames. Without one, a random seed is picked.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn build(self) -> Reveal {
+        let scenes = if self.scenes.is_empty() {
+            vec![Scene::new(self.style, &self.text, self.duration)]
+        } else {
+            self.scenes
+        };
+        let frame = Buffer::new(self.size.0, self.size.1);
+        let seed = self.seed.unwrap_or_else(rand::random);
+        let palette = self.palette.or_else(|| self.theme.palette.clone());
+        let text_style = TextStyle {
+            color: self.text_color.unwrap_or(TextColor::Fixed(self.theme.text.unwrap_or(TEXT_COLOR))),
+            effect: self.text_effect.unwrap_or(match self.theme.text_background {
+                Some(_) => TextEffect::Box,
+                None => TextEffect::None,
+            }),
+            backdrop: self.theme.text_background,
+        };
+        let mut rng = StdRng::seed_from_u64(seed);
+        let first = if self.shuffle { rng.gen_range(0..scenes.len()) } else { 0 };
+
+        Reveal {
+            current: Running::start(&scenes, first, Duration::ZERO, self.reduced_motion, palette.as_ref(), &mut rng),
+            scenes,
+            loops: self.loops,
+            text: None,
+            outgoing: frame.clone(),
+            incoming: frame.clone(),
+            frame,
+            previous: None,
+            position: 0,
+            progress: None,
+            progress_style: self.progress_style,
+            big_text: self.big_text,
+            shuffle: self.shuffle,
+            dim: self.dim,
+            clock: Duration::ZERO,
+            interlude: None,
+            paused: false,
+            motion: self.reduced_motion,
+            palette,
+            theme: self.theme,
+            text_style,
+            placement: self.placement,
+            blocks: self.blocks,
+            quality: 1.0,
+            limiter: (self.reduced_motion == ReducedMotion::Limit).then(|| Limiter::new(self.size.0, self.size.1)),
+            ending: false,
+            finished: false,
+            seed,
+            rng,
+        }
+    }
+}
@@ -1,67 +1,72 @@
-use rand::Rng;
-use std::{
-    io::{stdout, Write},
-    thread::sleep,
-    time::{Duration, Instant},
-};
-use termion::{
-    clear, color,
-    cursor::{self},
-};
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::io;
+use std::time::Duration;
+use termion::color;
+use std::sync::mpsc::Receiver;
 
-use crate::terminal::Terminal;
+use crate::animation::Animation;
+use crate::buffer::Buffer;
+use crate::cli::AnimationStyle;
+use crate::control::Control;
+use crate::palette::Palette;
+use crate::player::{play, PlayOptions, Scene};
+use crate::terminal::Cell;
 
-pub fn explosion_animation(text: &str, duration: Duration, term_signal: &Arc<AtomicBool>) {
-    let start = Instant::now();
-    let term = Terminal::new();
-    let mut stdout = stdout();
-    let mut rng = rand::thread_rng();
+pub struct Explosion {
+    seed: u64,
+    palette: Option<Palette>,
+}
 
-    // check if duration is 0 or if we've been running for longer than the duration
-    while duration.as_millis() == 0 || start.elapsed() < duration {
-        if term_signal.load(Ordering::Relaxed) {
-            return;  // Just return, cleanup handled in main
+impl Explosion {
+    /// An explosion whose sparks flicker the same way for the same `seed`.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            palette: None,
         }
-        
-        write!(stdout, "{}", clear::All).unwrap();
-        let time = start.elapsed().as_secs_f32();
+    }
+}
+
+impl Animation for Explosion {
+    fn render(&mut self, buffer: &mut Buffer, elapsed: Duration) {
+        // The sparks change color once a frame interval, the same way
+        // whenever that interval is drawn.
+        let step = (elapsed.as_millis() / self.frame_interval().as_millis()) as u64;
+        let rng = &mut StdRng::seed_from_u64(self.seed.wrapping_add(step));
+
+        buffer.clear();
+        let time = elapsed.as_secs_f32();
         let radius = (time * 10.0) as u16;
-        
-        let (center_x, center_y) = term.center_pos();
-        
+
+        let (center_x, center_y) = buffer.center_pos();
+
         for angle in (0..360).step_by(5) {
             let x = center_x as f32 + (angle as f32).to_radians().cos() * radius as f32;
             let y = center_y as f32 + (angle as f32).to_radians().sin() * radius as f32;
-            
-            if x >= 0.0 && x < term.width as f32 && y >= 0.0 && y < term.height as f32 {
-                let color = color::Rgb(
-                    rng.gen_range(200..=255),
-                    rng.gen_range(0..=100),
-                    0,
-                );
-                write!(
-                    stdout,
-                    "{}{}*",
-                    cursor::Goto(x as u16 + 1, y as u16 + 1),
-                    color::Fg(color)
-                )
-                .unwrap();
+
+            if x >= 0.0 && x < buffer.width as f32 && y >= 0.0 && y < buffer.height as f32 {
+                let color = match &self.palette {
+                    Some(palette) => palette.sample(rng.gen()),
+                    None => color::Rgb(
+                        rng.gen_range(200..=255),
+                        rng.gen_range(0..=100),
+                        0,
+                    ),
+                };
+                buffer.set(x as u16, y as u16, Cell { char: '*', color, background: None });
             }
         }
+    }
 
-        // Draw text in the center
-        write!(
-            stdout,
-            "{}{}{}",
-            cursor::Goto(center_x - (text.len() as u16 / 2), center_y),
-            color::Fg(color::White),
-            text
-        )
-        .unwrap();
-        
-        stdout.flush().unwrap();
-        sleep(Duration::from_millis(50));
+    fn frame_interval(&self) -> Duration {
+        Duration::from_millis(50)
     }
-}
\ No newline at end of file
+
+    fn set_palette(&mut self, palette: &Palette) {
+        self.palette = Some(palette.clone());
+    }
+}
+
+pub fn explosion_animation(text: &str, duration: Duration, controls: &Receiver<Control>) -> io::Result<()> {
+    play(&[Scene::new(AnimationStyle::Explosion, text, duration)], &PlayOptions::default(), controls)
+}
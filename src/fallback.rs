@@ -0,0 +1,42 @@
+use std::io::{self, Write};
+
+use termion::{color, style};
+
+use crate::player::Scene;
+
+const BANNER_COLOR: color::Rgb = color::Rgb(0, 170, 255);
+
+/// The texts a show would reveal, in order, without blanks or repeats.
+pub fn texts(scenes: &[Scene]) -> Vec<&str> {
+    let mut texts: Vec<&str> = scenes.iter().map(|scene| scene.text.as_str()).filter(|text| !text.is_empty()).collect();
+    texts.dedup();
+    texts
+}
+
+/// Writes the text without any animation, for logs, pipes and screen
+/// readers: one line per text, or each framed in a static colored banner.
+/// Color is left out when `NO_COLOR` is set.
+pub fn print<W: Write>(out: &mut W, texts: &[&str], banner: bool) -> io::Result<()> {
+    for text in texts {
+        if banner {
+            write_banner(out, text)?;
+        } else {
+            writeln!(out, "{}", text)?;
+        }
+    }
+    out.flush()
+}
+
+fn write_banner<W: Write>(out: &mut W, text: &str) -> io::Result<()> {
+    let colored = std::env::var_os("NO_COLOR").is_none();
+    let (paint, bold, reset) = if colored {
+        (color::Fg(BANNER_COLOR).to_string(), style::Bold.to_string(), style::Reset.to_string())
+    } else {
+        Default::default()
+    };
+
+    let rule = "─".repeat(text.chars().count() + 4);
+    writeln!(out, "{}┌{}┐{}", paint, rule, reset)?;
+    writeln!(out, "{}│{}  {}{}{}  {}│{}", paint, reset, bold, text, reset, paint, reset)?;
+    writeln!(out, "{}└{}┘{}", paint, rule, reset)
+}
@@ -0,0 +1,51 @@
+
+use crate::buffer::Buffer;
+use crate::layout::Placement;
+
+const HEIGHT: u16 = 5;
+
+/// 3x5 block glyphs for clock readouts, one string per row.
+fn glyph(c: char) -> Option<[&'static str; HEIGHT as usize]> {
+    Some(match c {
+        '0' => ["███", "█ █", "█ █", "█ █", "███"],
+        '1' => ["  █", "  █", "  █", "  █", "  █"],
+        '2' => ["███", "  █", "███", "█  ", "███"],
+        '3' => ["███", "  █", "███", "  █", "███"],
+        '4' => ["█ █", "█ █", "███", "  █", "  █"],
+        '5' => ["███", "█  ", "███", "  █", "███"],
+        '6' => ["███", "█  ", "███", "█ █", "███"],
+        '7' => ["███", "  █", "  █", "  █", "  █"],
+        '8' => ["███", "█ █", "███", "█ █", "███"],
+        '9' => ["███", "█ █", "███", "  █", "███"],
+        ':' => [" ", "█", " ", "█", " "],
+        ' ' => [" ", " ", " ", " ", " "],
+        _ => return None,
+    })
+}
+
+/// Where each block of `text` goes at `placement`, or `None` if the font
+/// can't draw it or it doesn't fit, for the caller to fall back to normal
+/// text.
+pub fn big_text_glyphs(buffer: &Buffer, text: &str, placement: &Placement) -> Option<Vec<(u16, u16, char)>> {
+    let glyphs = text.chars().map(glyph).collect::<Option<Vec<_>>>()?;
+    // One column of spacing between glyphs.
+    let width = glyphs.iter().map(|glyph| glyph[0].chars().count() as u16 + 1).sum::<u16>().saturating_sub(1);
+    if width > buffer.width || HEIGHT > buffer.height {
+        return None;
+    }
+
+    let mut blocks = Vec::new();
+    let (mut x, y) = placement.origin((buffer.width, buffer.height), (width, HEIGHT));
+    for glyph in glyphs {
+        for (row, line) in glyph.iter().enumerate() {
+            for (i, c) in line.chars().enumerate() {
+                let (bx, by) = (x + i as i32, y + row as i32);
+                if c != ' ' && (0..buffer.width as i32).contains(&bx) && (0..buffer.height as i32).contains(&by) {
+                    blocks.push((bx as u16, by as u16, '█'));
+                }
+            }
+        }
+        x += glyph[0].chars().count() as i32 + 1;
+    }
+    Some(blocks)
+}
@@ -1,32 +1,51 @@
-use std::ops::{Mul, Sub};
-use num_traits::{Float, FromPrimitive};
+use num_traits::Float;
+use termion::color;
 
-// Helper function to convert HSV to RGB
-pub fn hsv_to_rgb<T>(h: T, s: T, v: T) -> (u8, u8, u8)
-where
-    T: Float + FromPrimitive + Mul<Output = T> + Sub<Output = T>
-{
-    let six = T::from_f64(6.0).unwrap();
-    let one = T::from_f64(1.0).unwrap();
-    let n255 = T::from_f64(255.0).unwrap();
+use crate::buffer::Buffer;
+use crate::color::shade;
+use crate::terminal::Cell;
 
-    let h = h * six;
-    let i = h.floor();
-    let f = h - i;
-    let p = v * (one - s);
-    let q = v * (one - s * f);
-    let t = v * (one - s * (one - f));
+/// HSV to RGB for the styles' generic float math; see [`crate::color::hsv_to_rgb`].
+pub fn hsv_to_rgb<T: Float>(h: T, s: T, v: T) -> (u8, u8, u8) {
+    let part = |x: T| x.to_f32().unwrap_or(0.0);
+    let rgb = crate::color::hsv_to_rgb(part(h), part(s), part(v));
+    (rgb.0, rgb.1, rgb.2)
+}
 
-    let (r, g, b) = match i.to_i32().unwrap() % 6 {
-        0 => (v, t, p),
-        1 => (q, v, p),
-        2 => (p, v, t),
-        3 => (p, q, v),
-        4 => (t, p, v),
-        _ => (v, p, q),
-    };
+/// Fills the bottom row as a bar showing `progress` from 0.0 to 1.0, in
+/// `color`.
+pub fn draw_progress_bar(buffer: &mut Buffer, progress: f32, color: color::Rgb) {
+    let Some(y) = buffer.height.checked_sub(1) else { return };
+    let filled = (buffer.width as f32 * progress.clamp(0.0, 1.0)).round() as u16;
+    for x in 0..buffer.width {
+        let cell = if x < filled {
+            Cell { char: '█', color, background: None }
+        } else {
+            Cell { char: '░', color: color::Rgb(90, 90, 90), background: None }
+        };
+        buffer.set(x, y, cell);
+    }
+}
 
-    ((r * n255).to_u8().unwrap(),
-     (g * n255).to_u8().unwrap(),
-     (b * n255).to_u8().unwrap())
-}
\ No newline at end of file
+/// Blanks every column past `progress` (0.0 to 1.0) of the width, so the
+/// animation fills the screen from the left as progress grows.
+pub fn fill_progress(buffer: &mut Buffer, progress: f32) {
+    let filled = (buffer.width as f32 * progress.clamp(0.0, 1.0)).round() as u16;
+    for y in 0..buffer.height {
+        for x in filled..buffer.width {
+            buffer.set(x, y, Cell::default());
+        }
+    }
+}
+
+/// Scales every cell's colors by `brightness`, from 0.0 (black) to 1.0.
+pub fn dim(buffer: &mut Buffer, brightness: f32) {
+    for y in 0..buffer.height {
+        for x in 0..buffer.width {
+            let cell = *buffer.get(x, y);
+            let color = shade(cell.color, brightness);
+            let background = cell.background.map(|background| shade(background, brightness));
+            buffer.set(x, y, Cell { color, background, ..cell });
+        }
+    }
+}
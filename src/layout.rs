@@ -0,0 +1,266 @@
+use std::str::FromStr;
+
+use clap::ValueEnum;
+use serde::Deserialize;
+
+/// Where text goes across the screen.
+#[derive(ValueEnum, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Align {
+    Left,
+    #[default]
+    Center,
+    Right,
+}
+
+/// Where text goes down the screen.
+#[derive(ValueEnum, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum VAlign {
+    Top,
+    #[default]
+    Middle,
+    Bottom,
+}
+
+/// Space kept clear of text along each edge, in cells.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(try_from = "MarginsValue")]
+pub struct Margins {
+    pub top: u16,
+    pub right: u16,
+    pub bottom: u16,
+    pub left: u16,
+}
+
+/// Parses `--margin` like CSS: `ALL`, `VERTICAL,HORIZONTAL` or
+/// `TOP,RIGHT,BOTTOM,LEFT`.
+impl FromStr for Margins {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid margin '{}', expected N, V,H or TOP,RIGHT,BOTTOM,LEFT", s);
+        let sides = s
+            .split(',')
+            .map(|side| side.trim().parse::<u16>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| invalid())?;
+        match sides[..] {
+            [all] => Ok(Margins { top: all, right: all, bottom: all, left: all }),
+            [vertical, horizontal] => Ok(Margins { top: vertical, right: horizontal, bottom: vertical, left: horizontal }),
+            [top, right, bottom, left] => Ok(Margins { top, right, bottom, left }),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// `margin = 2` or `margin = "1,2"` in the config or a show file.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum MarginsValue {
+    All(u16),
+    Sides(String),
+}
+
+impl TryFrom<MarginsValue> for Margins {
+    type Error = String;
+
+    fn try_from(value: MarginsValue) -> Result<Self, Self::Error> {
+        match value {
+            MarginsValue::All(all) => Ok(Margins { top: all, right: all, bottom: all, left: all }),
+            MarginsValue::Sides(sides) => sides.parse(),
+        }
+    }
+}
+
+/// Parses `--offset`: `X,Y` in cells, negative for left or up.
+pub fn parse_offset(s: &str) -> Result<(i16, i16), String> {
+    s.split_once(',')
+        .and_then(|(x, y)| Some((x.trim().parse().ok()?, y.trim().parse().ok()?)))
+        .ok_or_else(|| format!("invalid offset '{}', expected X,Y like 0,-2", s))
+}
+
+/// Where a block of text goes: aligned inside the margins, then moved by
+/// the offset. The default is the middle of the screen.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Placement {
+    pub align: Align,
+    pub valign: VAlign,
+    pub offset: (i16, i16),
+    pub margins: Margins,
+}
+
+impl Placement {
+    /// The top-left corner of a `size` block in an `area` (both width by
+    /// height). It may lie off the area; a block too wide for it keeps its
+    /// start in view unless it is right-aligned.
+    pub fn origin(&self, area: (u16, u16), size: (u16, u16)) -> (i32, i32) {
+        let Margins { top, right, bottom, left } = self.margins;
+        let inner_width = area.0 as i32 - left as i32 - right as i32;
+        let inner_height = area.1 as i32 - top as i32 - bottom as i32;
+        let (width, height) = (size.0 as i32, size.1 as i32);
+
+        let x = match self.align {
+            Align::Left => 0,
+            Align::Center => ((inner_width - width) / 2).max(0),
+            Align::Right => inner_width - width,
+        };
+        let y = match self.valign {
+            VAlign::Top => 0,
+            VAlign::Middle => ((inner_height - height) / 2).max(0),
+            VAlign::Bottom => inner_height - height,
+        };
+        (left as i32 + x + self.offset.0 as i32, top as i32 + y + self.offset.1 as i32)
+    }
+
+    /// Where each character of `text` goes in an `area`. Each line is
+    /// aligned on its own; characters off the area are left out.
+    pub fn glyphs(&self, area: (u16, u16), text: &str) -> Vec<(u16, u16, char)> {
+        let lines: Vec<&str> = text.lines().collect();
+        let height = lines.len() as u16;
+        let mut glyphs = Vec::new();
+        for (row, line) in lines.iter().enumerate() {
+            let (x, y) = self.origin(area, (line.chars().count() as u16, height));
+            let y = y + row as i32;
+            for (i, char) in line.chars().enumerate() {
+                let x = x + i as i32;
+                if (0..area.0 as i32).contains(&x) && (0..area.1 as i32).contains(&y) {
+                    glyphs.push((x as u16, y as u16, char));
+                }
+            }
+        }
+        glyphs
+    }
+}
+
+/// Text placed on its own besides the scene's, e.g. a subtitle along the
+/// bottom. In a config or show file it is written as for `--block`.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct TextBlock {
+    pub text: String,
+    pub align: Align,
+    pub valign: VAlign,
+    pub offset: (i16, i16),
+    /// The block's own margins; without them it keeps to the scene text's.
+    pub margins: Option<Margins>,
+}
+
+impl TextBlock {
+    /// Where the block goes, with `margins` unless it has its own.
+    pub fn placement(&self, margins: Margins) -> Placement {
+        Placement {
+            align: self.align,
+            valign: self.valign,
+            offset: self.offset,
+            margins: self.margins.unwrap_or(margins),
+        }
+    }
+}
+
+/// Parses `--block`: `TEXT[:align=..][:valign=..][:offset=X,Y][:margin=..]`.
+/// The text may itself contain `:`; only known `key=value` fields at the end
+/// are taken as options.
+impl FromStr for TextBlock {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut fields: Vec<&str> = s.split(':').collect();
+        let mut block = TextBlock::default();
+        while fields.len() > 1 {
+            let Some((key, value)) = fields[fields.len() - 1].split_once('=') else { break };
+            match key.trim() {
+                "align" => block.align = Align::from_str(value, true).map_err(|_| format!("unknown align '{}'", value))?,
+                "valign" => {
+                    block.valign = VAlign::from_str(value, true).map_err(|_| format!("unknown valign '{}'", value))?
+                }
+                "offset" => block.offset = parse_offset(value)?,
+                "margin" => block.margins = Some(value.parse()?),
+                _ => break,
+            }
+            fields.pop();
+        }
+        block.text = fields.join(":");
+        Ok(block)
+    }
+}
+
+impl TryFrom<String> for TextBlock {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blocks_take_options_from_the_end() {
+        let block: TextBlock = "v1.2:valign=bottom:align=right:offset=-1,2:margin=1,2".parse().unwrap();
+        assert_eq!(block.text, "v1.2");
+        assert_eq!((block.align, block.valign, block.offset), (Align::Right, VAlign::Bottom, (-1, 2)));
+        assert_eq!(block.margins, Some(Margins { top: 1, right: 2, bottom: 1, left: 2 }));
+    }
+
+    #[test]
+    fn block_text_may_contain_colons() {
+        let block: TextBlock = "Starts at 10:30:align=left".parse().unwrap();
+        assert_eq!((block.text.as_str(), block.align), ("Starts at 10:30", Align::Left));
+        let block: TextBlock = "a=b:c".parse().unwrap();
+        assert_eq!(block, TextBlock { text: "a=b:c".to_string(), ..TextBlock::default() });
+    }
+
+    #[test]
+    fn bad_block_options_are_rejected() {
+        assert!("x:align=middle".parse::<TextBlock>().is_err());
+        assert!("x:valign=left".parse::<TextBlock>().is_err());
+        assert!("x:offset=1".parse::<TextBlock>().is_err());
+        assert!("x:margin=1,2,3".parse::<TextBlock>().is_err());
+    }
+
+    #[test]
+    fn blocks_fall_back_to_the_given_margins() {
+        let margins = Margins { top: 1, right: 1, bottom: 1, left: 1 };
+        let block: TextBlock = "x".parse().unwrap();
+        assert_eq!(block.placement(margins).margins, margins);
+        let block: TextBlock = "x:margin=3".parse().unwrap();
+        assert_eq!(block.placement(margins).margins.top, 3);
+    }
+
+    #[test]
+    fn offsets_and_margins_parse_like_the_flags() {
+        assert_eq!(parse_offset(" 3, -2 "), Ok((3, -2)));
+        assert!(parse_offset("3").is_err());
+        assert_eq!("2".parse(), Ok(Margins { top: 2, right: 2, bottom: 2, left: 2 }));
+        assert_eq!("1,4,2,3".parse(), Ok(Margins { top: 1, right: 4, bottom: 2, left: 3 }));
+        assert!("a".parse::<Margins>().is_err());
+    }
+
+    #[test]
+    fn origin_aligns_inside_the_margins_then_offsets() {
+        let area = (20, 10);
+        let placement = |align, valign| Placement { align, valign, ..Placement::default() };
+        assert_eq!(placement(Align::Center, VAlign::Middle).origin(area, (4, 2)), (8, 4));
+        assert_eq!(placement(Align::Right, VAlign::Bottom).origin(area, (4, 2)), (16, 8));
+        let placement = Placement {
+            offset: (1, -1),
+            margins: Margins { top: 2, right: 0, bottom: 0, left: 3 },
+            ..placement(Align::Left, VAlign::Top)
+        };
+        assert_eq!(placement.origin(area, (4, 2)), (4, 1));
+        // Too wide to center: the start stays in view.
+        assert_eq!(Placement::default().origin((4, 1), (10, 1)).0, 0);
+    }
+
+    #[test]
+    fn glyphs_align_each_line_and_drop_what_is_off_screen() {
+        let right = Placement { align: Align::Right, ..Placement::default() };
+        let glyphs = right.glyphs((5, 2), "ab\nc");
+        assert_eq!(glyphs, [(3, 0, 'a'), (4, 0, 'b'), (4, 1, 'c')]);
+        let off = Placement { offset: (4, 0), ..right };
+        assert_eq!(off.glyphs((5, 1), "ab"), []);
+    }
+}
@@ -0,0 +1,228 @@
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+
+use clap::ValueEnum;
+use serde::Deserialize;
+use termion::color::Rgb;
+
+use crate::buffer::Buffer;
+use crate::color::{contrast_ratio, mix, parse_hex};
+use crate::terminal::Cell;
+
+const WHITE: Rgb = Rgb(255, 255, 255);
+const BLACK: Rgb = Rgb(0, 0, 0);
+
+/// How much of the box color a translucent box lays over the animation.
+const TRANSLUCENCY: f32 = 0.65;
+
+/// How strongly a glow tints the cells one and two away from the text.
+const GLOW: [f32; 2] = [0.6, 0.3];
+
+/// How overlaid text is set off from the animation behind it.
+#[derive(ValueEnum, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum TextEffect {
+    /// The text as it is.
+    #[default]
+    None,
+    /// A ring of the backdrop color around every letter.
+    Outline,
+    /// A copy of the text in the backdrop color, one cell down and right.
+    Shadow,
+    /// A solid box of the backdrop color behind the text.
+    Box,
+    /// A box that lets the animation show through, darkened or lightened
+    /// towards the backdrop color.
+    TranslucentBox,
+    /// The animation around the text tinted towards the text color.
+    Glow,
+}
+
+impl FromStr for TextEffect {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        <Self as ValueEnum>::from_str(s, true).map_err(|_| format!("unknown text effect '{}'", s))
+    }
+}
+
+/// The color text is drawn in.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum TextColor {
+    Fixed(Rgb),
+    /// Black or white for each character, whichever has the higher contrast
+    /// ratio with the cell it covers. The text then sits on that cell's color.
+    Auto,
+}
+
+impl Default for TextColor {
+    fn default() -> Self {
+        TextColor::Fixed(WHITE)
+    }
+}
+
+/// Parses `--text-color`: `auto` or `#rrggbb`.
+impl FromStr for TextColor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim().eq_ignore_ascii_case("auto") {
+            return Ok(TextColor::Auto);
+        }
+        parse_hex(s)
+            .map(TextColor::Fixed)
+            .map_err(|_| format!("invalid text color '{}', expected #rrggbb or auto", s))
+    }
+}
+
+impl TryFrom<String> for TextColor {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+/// How the engine draws text over the animation.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TextStyle {
+    pub color: TextColor,
+    pub effect: TextEffect,
+    /// The outline, shadow and box color. Without one, black or white is
+    /// picked to contrast with the text.
+    pub backdrop: Option<Rgb>,
+}
+
+impl TextStyle {
+    /// Draws `glyphs`, each a character at `(x, y)`, with the effect laid
+    /// down first. Glyphs off the buffer are skipped.
+    pub fn draw(&self, buffer: &mut Buffer, glyphs: &[(u16, u16, char)]) {
+        let glyphs: Vec<_> = glyphs
+            .iter()
+            .copied()
+            .filter(|&(x, y, _)| x < buffer.width && y < buffer.height)
+            .collect();
+        let text: HashSet<(u16, u16)> = glyphs.iter().map(|&(x, y, _)| (x, y)).collect();
+        let letters: Vec<(u16, u16, char)> = glyphs.iter().copied().filter(|&(_, _, c)| c != ' ').collect();
+        let backdrop = self.backdrop();
+
+        match self.effect {
+            TextEffect::None => {}
+            TextEffect::Outline => {
+                for (x, y) in around(buffer, &letters, 1).into_iter().map(|(x, y, _)| (x, y)).chain(text.iter().copied()) {
+                    let cell = *buffer.get(x, y);
+                    let char = if text.contains(&(x, y)) { cell.char } else { ' ' };
+                    buffer.set(x, y, Cell { char, background: Some(backdrop), ..cell });
+                }
+            }
+            TextEffect::Shadow => {
+                for &(x, y, char) in &letters {
+                    let (x, y) = (x.saturating_add(1), y.saturating_add(1));
+                    if !text.contains(&(x, y)) {
+                        buffer.set(x, y, Cell { char, color: backdrop, background: None });
+                    }
+                }
+            }
+            TextEffect::Box | TextEffect::TranslucentBox => {
+                for (x, y) in box_cells(buffer, &glyphs) {
+                    let cell = *buffer.get(x, y);
+                    let cell = if self.effect == TextEffect::Box {
+                        Cell { char: ' ', color: cell.color, background: Some(backdrop) }
+                    } else {
+                        let under = covered(&cell).map_or(backdrop, |under| mix(under, backdrop, TRANSLUCENCY));
+                        Cell { color: mix(cell.color, backdrop, TRANSLUCENCY), background: Some(under), ..cell }
+                    };
+                    buffer.set(x, y, cell);
+                }
+            }
+            TextEffect::Glow => {
+                let tint = match self.color {
+                    TextColor::Fixed(color) => color,
+                    TextColor::Auto => WHITE,
+                };
+                for (x, y, distance) in around(buffer, &letters, GLOW.len() as u16) {
+                    let cell = *buffer.get(x, y);
+                    let strength = GLOW[distance as usize - 1];
+                    // Empty cells light up as a faint haze.
+                    let (char, color) = if cell.char == ' ' { ('░', BLACK) } else { (cell.char, cell.color) };
+                    buffer.set(x, y, Cell { char, color: mix(color, tint, strength), ..cell });
+                }
+            }
+        }
+
+        for (x, y, char) in glyphs {
+            let under = *buffer.get(x, y);
+            let cell = match self.color {
+                TextColor::Fixed(color) => Cell { char, color, background: under.background },
+                TextColor::Auto => {
+                    let background = covered(&under);
+                    Cell { char, color: opposite(background.unwrap_or(BLACK)), background }
+                }
+            };
+            buffer.set(x, y, cell);
+        }
+    }
+
+    /// The effect color: the style's own, or black or white, whichever
+    /// stands out more from the text.
+    pub fn backdrop(&self) -> Rgb {
+        match (self.backdrop, self.color) {
+            (Some(backdrop), _) => backdrop,
+            (None, TextColor::Fixed(color)) => opposite(color),
+            (None, TextColor::Auto) => BLACK,
+        }
+    }
+}
+
+/// The color `cell` puts behind a character drawn over it: its background,
+/// or the color of whatever it shows. `None` for an empty cell.
+fn covered(cell: &Cell) -> Option<Rgb> {
+    cell.background.or((cell.char != ' ').then_some(cell.color))
+}
+
+/// Black or white, whichever has the higher contrast ratio with `color`.
+fn opposite(color: Rgb) -> Rgb {
+    if contrast_ratio(color, WHITE) >= contrast_ratio(color, BLACK) {
+        WHITE
+    } else {
+        BLACK
+    }
+}
+
+/// Cells up to `reach` away from any of `letters` (diagonals count as one)
+/// that aren't letters themselves, with how far away they are.
+fn around(buffer: &Buffer, letters: &[(u16, u16, char)], reach: u16) -> Vec<(u16, u16, u16)> {
+    let positions: HashSet<(u16, u16)> = letters.iter().map(|&(x, y, _)| (x, y)).collect();
+    let mut nearest: HashMap<(u16, u16), u16> = HashMap::new();
+    for &(x, y, _) in letters {
+        for ny in y.saturating_sub(reach)..=y.saturating_add(reach).min(buffer.height - 1) {
+            for nx in x.saturating_sub(reach)..=x.saturating_add(reach).min(buffer.width - 1) {
+                if positions.contains(&(nx, ny)) {
+                    continue;
+                }
+                let distance = nx.abs_diff(x).max(ny.abs_diff(y));
+                let entry = nearest.entry((nx, ny)).or_insert(distance);
+                *entry = (*entry).min(distance);
+            }
+        }
+    }
+    nearest.into_iter().map(|((x, y), distance)| (x, y, distance)).collect()
+}
+
+/// Every row with text, from one cell left of its first glyph to one right
+/// of its last.
+fn box_cells(buffer: &Buffer, glyphs: &[(u16, u16, char)]) -> Vec<(u16, u16)> {
+    let mut rows: Vec<(u16, u16, u16)> = Vec::new();
+    for &(x, y, _) in glyphs {
+        match rows.iter_mut().find(|(row, _, _)| *row == y) {
+            Some((_, start, end)) => (*start, *end) = ((*start).min(x), (*end).max(x)),
+            None => rows.push((y, x, x)),
+        }
+    }
+    rows.into_iter()
+        .flat_map(|(y, start, end)| {
+            (start.saturating_sub(1)..=end.saturating_add(1).min(buffer.width - 1)).map(move |x| (x, y))
+        })
+        .collect()
+}
@@ -4,4 +4,42 @@ pub mod cli;
 pub mod rainbow;
 pub mod explosion;
 pub mod mandelbrot;
-pub mod helper;
\ No newline at end of file
+pub mod helper;
+pub mod sequence;
+pub mod animation;
+pub mod buffer;
+pub mod renderer;
+pub mod player;
+pub mod transition;
+pub mod show;
+pub mod loader;
+pub mod config;
+pub mod text;
+pub mod engine;
+pub mod control;
+pub mod progress;
+pub mod clock;
+pub mod font;
+pub mod screensaver;
+pub mod remote;
+pub mod serve;
+pub mod motion;
+pub mod pacing;
+pub mod stats;
+pub mod bandwidth;
+pub mod color;
+pub mod palette;
+pub mod theme;
+pub mod legibility;
+pub mod layout;
+pub mod fallback;
+
+pub use buffer::Buffer;
+pub use cli::AnimationStyle;
+pub use control::{spawn, Control, RevealHandle};
+pub use engine::{ProgressStyle, Reveal, RevealBuilder};
+pub use palette::Palette;
+pub use theme::Theme;
+pub use player::{PlayOptions, Scene};
+pub use terminal::{Cell, Rect};
+pub use transition::{Transition, TransitionKind};
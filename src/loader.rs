@@ -0,0 +1,51 @@
+use std::{fmt, fs, io, path::Path};
+
+use serde::de::DeserializeOwned;
+
+/// Why a TOML or JSON file (a show, the config, ...) couldn't be used.
+#[derive(Debug)]
+pub enum LoadError {
+    Io(io::Error),
+    /// Syntax or type errors, with the offending field path.
+    Parse { field: String, message: String },
+    /// Well-formed files whose values still don't make sense.
+    Invalid { field: String, message: String },
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Io(err) => write!(f, "{}", err),
+            LoadError::Parse { field, message } if field == "." => write!(f, "{}", message),
+            LoadError::Parse { field, message } | LoadError::Invalid { field, message } => {
+                write!(f, "{}: {}", field, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+/// Reads `path`, picking JSON for `.json` files and TOML otherwise.
+pub fn load<T: DeserializeOwned>(path: &Path) -> Result<T, LoadError> {
+    let source = fs::read_to_string(path).map_err(LoadError::Io)?;
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("json") => from_json(&source),
+        _ => from_toml(&source),
+    }
+}
+
+pub fn from_toml<T: DeserializeOwned>(source: &str) -> Result<T, LoadError> {
+    serde_path_to_error::deserialize(toml::Deserializer::new(source)).map_err(|err| LoadError::Parse {
+        field: err.path().to_string(),
+        message: err.into_inner().to_string().trim_end().to_string(),
+    })
+}
+
+pub fn from_json<T: DeserializeOwned>(source: &str) -> Result<T, LoadError> {
+    let mut de = serde_json::Deserializer::from_str(source);
+    serde_path_to_error::deserialize(&mut de).map_err(|err| LoadError::Parse {
+        field: err.path().to_string(),
+        message: err.into_inner().to_string(),
+    })
+}
@@ -1,62 +1,325 @@
-use std::{
-    io::stdout,
-    time::Duration,
-};
+use std::io::{self, stdin, stdout, Read};
 use termion::{
-    clear,
     raw::IntoRawMode,
     event::Key,
     input::TermRead,
 };
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
-
-use clap::Parser;
-
-use reveal::cli::{Args, AnimationStyle};
-use reveal::rainbow::rainbow_animation;
-use reveal::explosion::explosion_animation;
-use reveal::waves::waves_animation;
-use reveal::waves::waves_gradient_animation;
-use reveal::mandelbrot::mandelbrot_animation;
-use reveal::mandelbrot::mandelbrot_matrix_animation;
-use reveal::mandelbrot::mandelbrot_fast_animation;
+use std::sync::mpsc::{self, Sender};
+use std::time::{Duration, Instant};
+
+use chrono::NaiveTime;
+use clap::{error::ErrorKind, parser::ValueSource, ArgMatches, CommandFactory, FromArgMatches, ValueEnum};
+
+use reveal::cli::{Args, Command};
+use reveal::clock;
+use reveal::config::{Config, Settings};
+use reveal::fallback;
+use reveal::layout::Placement;
+use reveal::control::{default_outro, Control};
+use reveal::engine::ProgressStyle;
+use reveal::loader::LoadError;
+use reveal::player::{play, PlayOptions, Scene};
+use reveal::progress;
+use reveal::sequence::{SceneDefaults, Segment};
+use reveal::show::Show;
+use reveal::remote;
+use reveal::screensaver;
+use reveal::serve;
+use reveal::text;
+use reveal::theme::Theme;
+use reveal::transition::TransitionKind;
+use reveal::AnimationStyle;
+
 fn main() {
-    let args = Args::parse();
-    let duration = Duration::from_millis(args.duration);
-    
-    let term = Arc::new(AtomicBool::new(false));
-    let term_clone = Arc::clone(&term);
+    let matches = Args::command().get_matches();
+    let mut args = Args::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+    if let Some(Command::Ctl { socket, request }) = &args.command {
+        if let Err(err) = remote::send(socket, request) {
+            eprintln!("error: {}: {}", socket.display(), err);
+            std::process::exit(1);
+        }
+        return;
+    }
+    if args.listen.is_some() && matches!(args.command, Some(Command::Screensaver { .. } | Command::Serve { .. })) {
+        // Neither has a single player to send commands to.
+        Args::command()
+            .error(ErrorKind::ArgumentConflict, "--listen cannot be used with screensaver or serve")
+            .exit();
+    }
+    let (controls, receiver) = mpsc::channel();
+    if let Some(text) = read_text(&args, &matches, &controls) {
+        args.text = Some(text);
+    }
+    let (mut scenes, loops, settings) = scenes(&args, &matches);
+    let margins = settings.margin.unwrap_or_default();
+    let mut options = PlayOptions {
+        loops,
+        inline_height: settings.inline,
+        viewport: settings.viewport,
+        border: settings.border.unwrap_or(false),
+        title: settings.title.clone(),
+        outro: None,
+        progress_style: ProgressStyle::Bar,
+        big_text: settings.big_text.unwrap_or(false),
+        shuffle: false,
+        dim: None,
+        reduced_motion: settings.reduced_motion.unwrap_or_default(),
+        palette: settings.palette.clone(),
+        theme: match &settings.theme {
+            Some(path) => Theme::load(path).unwrap_or_else(|err| {
+                eprintln!("error: {}: {}", path.display(), err);
+                std::process::exit(1);
+            }),
+            None => Theme::default(),
+        },
+        text_color: settings.text_color,
+        text_effect: settings.text_effect,
+        placement: Placement {
+            align: settings.align.unwrap_or_default(),
+            valign: settings.valign.unwrap_or_default(),
+            offset: settings.offset.unwrap_or_default(),
+            margins,
+        },
+        blocks: settings.blocks.clone().unwrap_or_default(),
+        fps: settings.fps,
+        stats: settings.stats.unwrap_or(false),
+        max_bandwidth: settings.max_bandwidth,
+        seed: settings.seed,
+    };
+    if (args.accessible || !termion::is_tty(&stdout())) && !matches!(args.command, Some(Command::Serve { .. })) {
+        let mut out = stdout();
+        let result = match &args.command {
+            Some(Command::Progress { .. }) => progress::print_stdin(&mut out),
+            Some(Command::Countdown { until, seconds, .. }) => {
+                let remaining = clock::format_remaining(countdown_length(*until, *seconds));
+                fallback::print(&mut out, &[&remaining], args.banner)
+            }
+            Some(Command::Clock) => fallback::print(&mut out, &[&clock::current_time()], args.banner),
+            _ => fallback::print(&mut out, &fallback::texts(&scenes), args.banner),
+        };
+        if let Err(err) = result {
+            if err.kind() != io::ErrorKind::BrokenPipe {
+                eprintln!("error: stdout: {}", err);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    match &args.command {
+        Some(Command::Progress { outro }) => {
+            play_until_finished(&mut scenes, &mut options);
+            options.outro = Some(Scene { style: outro.clone(), ..default_outro() });
+            options.progress_style = ProgressStyle::Fill;
+            let _ = controls.send(Control::SetProgress(0.0));
+            progress::follow_stdin(controls.clone());
+        }
+        Some(Command::Countdown { until, seconds, finale }) => {
+            play_until_finished(&mut scenes, &mut options);
+            options.outro = Some(Scene { style: finale.clone(), ..default_outro() });
+            options.big_text = true;
+            let remaining = countdown_length(*until, *seconds);
+            clock::count_down(Instant::now() + remaining, controls.clone());
+        }
+        Some(Command::Clock) => {
+            play_until_finished(&mut scenes, &mut options);
+            options.big_text = true;
+            clock::tick_clock(controls.clone());
+        }
+        Some(Command::Screensaver { idle, messages, dim }) => {
+            let messages = match messages {
+                Some(path) => screensaver::load_messages(path).unwrap_or_else(|err| {
+                    eprintln!("error: {}: {}", path.display(), err);
+                    std::process::exit(1);
+                }),
+                None => Vec::new(),
+            };
+            options.loops = 0;
+            options.shuffle = true;
+            options.dim = (*dim > 0).then(|| Duration::from_secs(*dim));
+            let idle = (*idle > 0).then(|| Duration::from_secs(*idle));
+
+            let _raw = stdout().into_raw_mode().unwrap();
+            let (keys, key_receiver) = mpsc::channel();
+            std::thread::spawn(move || {
+                for key in key_input().keys().flatten() {
+                    if keys.send(key).is_err() {
+                        break;
+                    }
+                }
+            });
+            screensaver::run(&scenes, &options, &messages, idle, &key_receiver);
+            return;
+        }
+        Some(Command::Serve { port, bind }) => {
+            play_until_finished(&mut scenes, &mut options);
+            if let Err(err) = serve::serve((bind.as_str(), *port), scenes, options) {
+                eprintln!("error: {}:{}: {}", bind, port, err);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some(Command::Play { .. }) | Some(Command::Ctl { .. }) | None => {}
+    }
+    let _listener = args.listen.as_ref().map(|path| {
+        remote::listen(path, controls.clone()).unwrap_or_else(|err| {
+            eprintln!("error: {}: {}", path.display(), err);
+            std::process::exit(1);
+        })
+    });
 
-    let _raw = stdout().into_raw_mode().unwrap();
-    let stdin = std::io::stdin();
+    let raw = stdout().into_raw_mode().unwrap();
+    let keys = key_input();
 
     // Spawn input handling thread
     std::thread::spawn(move || {
-        for c in stdin.keys() {
+        for c in keys.keys() {
             if let Ok(Key::Ctrl('c')) = c {
-                term_clone.store(true, Ordering::SeqCst);
+                let _ = controls.send(Control::Cancel);
                 break;
             }
         }
     });
 
-    print!("{}{}", termion::cursor::Hide, clear::All);
-    
-    match args.style {
-        AnimationStyle::Rainbow => rainbow_animation(&args.text, duration, &term),
-        AnimationStyle::Explosion => explosion_animation(&args.text, duration, &term),
-        AnimationStyle::Waves => waves_animation(&args.text, duration, &term),
-        AnimationStyle::WavesGradient => waves_gradient_animation(&args.text, duration, &term),
-        AnimationStyle::Mandelbrot => mandelbrot_animation(&args.text, duration, &term),
-        AnimationStyle::MandelbrotMatrix => mandelbrot_matrix_animation(&args.text, duration, &term),
-        AnimationStyle::MandelbrotFast => mandelbrot_fast_animation(&args.text, duration, &term),
-    }
-
-    // Cleanup remains the same
-    print!("{}{}{}", 
-        termion::screen::ToMainScreen,
-        termion::cursor::Show,
-        clear::All
-    );
-} 
\ No newline at end of file
+    let result = play(&scenes, &options, &receiver);
+    drop(raw);
+    if let Err(err) = result {
+        if err.kind() != io::ErrorKind::BrokenPipe {
+            eprintln!("error: stdout: {}", err);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Where keys come from: when the text is piped in, they have to come from
+/// the terminal itself.
+fn key_input() -> Box<dyn Read + Send> {
+    if termion::is_tty(&stdin()) {
+        Box::new(stdin())
+    } else {
+        Box::new(termion::get_tty().unwrap())
+    }
+}
+
+/// How long `reveal countdown` counts down for.
+fn countdown_length(until: Option<NaiveTime>, seconds: Option<u64>) -> Duration {
+    match (until, seconds) {
+        (Some(time), _) => clock::until(time),
+        (None, seconds) => Duration::from_secs(seconds.unwrap_or_default()),
+    }
+}
+
+/// Keeps the scenes playing until the driving command finishes the show,
+/// however long that takes.
+fn play_until_finished(scenes: &mut [Scene], options: &mut PlayOptions) {
+    if let [scene] = scenes {
+        scene.duration = Duration::ZERO;
+    }
+    options.loops = 0;
+}
+
+/// Reads the text from stdin or `--text-file` when asked to, starting a
+/// watcher that sends updates to the player with `--watch`. `--text-file`
+/// wins over a `REVEAL_TEXT` from the environment.
+fn read_text(args: &Args, matches: &ArgMatches, controls: &Sender<Control>) -> Option<String> {
+    if args.text_file.is_some() && matches.value_source("text") == Some(ValueSource::CommandLine) {
+        Args::command()
+            .error(ErrorKind::ArgumentConflict, "--text-file cannot be used with --text")
+            .exit();
+    }
+    let from_stdin = args.text.as_deref() == Some("-") && args.text_file.is_none();
+    if from_stdin && matches!(args.command, Some(Command::Progress { .. })) {
+        Args::command()
+            .error(ErrorKind::ArgumentConflict, "progress reads stdin itself; --text - cannot be used with it")
+            .exit();
+    }
+    let (source, result) = match (&args.text_file, from_stdin, args.watch) {
+        (Some(path), _, true) => (path.display().to_string(), text::watch_file(path.clone(), controls.clone())),
+        (Some(path), _, false) => (path.display().to_string(), text::read_file(path)),
+        (None, true, true) => ("stdin".to_string(), text::watch_stdin(controls.clone())),
+        (None, true, false) => ("stdin".to_string(), text::read_stdin()),
+        (None, false, true) => Args::command()
+            .error(ErrorKind::MissingRequiredArgument, "--watch needs --text - or --text-file")
+            .exit(),
+        (None, false, false) => return None,
+    };
+    match result {
+        Ok(text) => Some(text),
+        Err(err) => {
+            eprintln!("error: {}: {}", source, err);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Resolves what to play, either from a show file or from the command line,
+/// layered over the selected preset and the config file, along with the
+/// settings for everything else.
+fn scenes(args: &Args, matches: &ArgMatches) -> (Vec<Scene>, u32, Settings) {
+    let config_path = args.config.clone().or_else(|| Config::default_path().filter(|path| path.exists()));
+    let config = match &config_path {
+        Some(path) => Config::load(path).unwrap_or_else(|err| fail(&path.display().to_string(), err)),
+        None => Config::default(),
+    };
+    let fallback = config
+        .settings(args.preset.as_deref())
+        .unwrap_or_else(|err| fail("config", err));
+    let cli = args.settings();
+    if let Some(conflict) = args.style_conflict(matches) {
+        Args::command().error(ErrorKind::ArgumentConflict, conflict).exit();
+    }
+
+    match &args.command {
+        Some(Command::Play { file }) => {
+            let show = Show::load(file).unwrap_or_else(|err| fail(&file.display().to_string(), err));
+            let scenes = show.scenes(&cli.scene_defaults(), &fallback.scene_defaults());
+            let settings = cli.or(show.settings.clone()).or(fallback);
+            let loops = settings.loops.unwrap_or(1);
+            (scenes, loops, settings)
+        }
+        Some(Command::Screensaver { .. }) => {
+            let settings = cli.or(fallback);
+            let segments = match &args.sequence {
+                Some(sequence) => sequence.0.clone(),
+                None => AnimationStyle::value_variants().iter().cloned().map(Segment::new).collect(),
+            };
+            let defaults = settings.scene_defaults().or(SceneDefaults {
+                transition: Some(TransitionKind::Crossfade),
+                ..SceneDefaults::default()
+            });
+            let scenes = segments
+                .iter()
+                .map(|segment| segment.resolve(&SceneDefaults::default(), &defaults))
+                .collect();
+            (scenes, 0, settings)
+        }
+        _ => {
+            let settings = cli.or(fallback);
+            let segments = match &args.sequence {
+                Some(sequence) => sequence.0.clone(),
+                None => {
+                    let Some(style) = settings.style.clone() else {
+                        Args::command()
+                            .error(
+                                ErrorKind::MissingRequiredArgument,
+                                "--style is required unless --sequence, a preset or the config file sets it",
+                            )
+                            .exit();
+                    };
+                    vec![Segment::new(style)]
+                }
+            };
+            let scenes = segments
+                .iter()
+                .map(|segment| segment.resolve(&SceneDefaults::default(), &settings.scene_defaults()))
+                .collect();
+            let loops = settings.loops.unwrap_or(1);
+            (scenes, loops, settings)
+        }
+    }
+}
+
+fn fail(context: &str, err: LoadError) -> ! {
+    eprintln!("error: {}: {}", context, err);
+    std::process::exit(1);
+}
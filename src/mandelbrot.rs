@@ -1,308 +1,232 @@
-use std::{
-    io::{stdout, Write},
-    thread::sleep,
-    time::{Duration, Instant},
-};
-use termion::{
-    clear, color,
-    cursor::{self},
-};
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
-
-use crate::terminal::Terminal;
+use std::io;
+use std::time::Duration;
+use termion::color;
+use std::sync::mpsc::Receiver;
+use num_traits::{Float, FromPrimitive};
+
+use crate::animation::Animation;
+use crate::buffer::Buffer;
+use crate::cli::AnimationStyle;
+use crate::control::Control;
+use crate::player::{play, PlayOptions, Scene};
+use crate::terminal::Cell;
 use crate::helper::hsv_to_rgb;
+use crate::palette::Palette;
+
+const MAX_ITER: u32 = 100;
+/// The fewest iterations adaptive quality goes down to.
+const MIN_ITER: u32 = 20;
+const CENTER_REAL: f64 = -0.5;
+const CENTER_IMAG: f64 = 0.0;
+/// How fast the zooming styles' time runs against the clock: they were
+/// designed stepping 0.016 per 32 ms frame.
+const TIME_SCALE: f64 = 0.5;
+
+/// Iterations to spend per cell at `quality` (0.0 to 1.0).
+fn max_iter(quality: f32) -> u32 {
+    ((MAX_ITER as f32 * quality.clamp(0.0, 1.0)).round() as u32).max(MIN_ITER)
+}
 
-pub fn mandelbrot_animation(text: &str, duration: Duration, term_signal: &Arc<AtomicBool>) {
-    let start = Instant::now();
-    let term = Terminal::new();
-    let mut stdout = stdout();
-    let (center_x, center_y) = term.center_pos();
-
-    // Calculate text boundaries
-    let text_start_x = center_x - (text.len() as u16 / 2);
-    let text_end_x = text_start_x + text.len() as u16;
-
-    write!(
-        stdout,
-        "{}{}{}",
-        termion::cursor::Hide,
-        clear::All,
-        termion::screen::ToAlternateScreen
-    ).unwrap();
-
-    let max_iter = 100;
-    let zoom_speed = 0.2;
-    let zoom_reset_threshold = 50.0;
-    let mut time = 0.0;
-    let center_real = -0.5;
-    let center_imag = 0.0;
-
-    while duration.as_millis() == 0 || start.elapsed() < duration {
-        if term_signal.load(Ordering::Relaxed) {
-            return;
-        }
-
-        // Update time and handle zoom reset
-        time += 0.016; // Approximately 60fps
-        let zoom = (0.05_f64 + time * zoom_speed).exp();
-        if zoom > zoom_reset_threshold {
-            time = 0.0;
-        }
-        
-        for y in 1..=term.height {
-            for x in 1..=term.width {
-                if y == center_y && x >= text_start_x && x <= text_end_x {
-                    continue;
-                }
+/// Renders the set at `zoom`, cycling the colors with `time`, iterating
+/// each cell up to `max_iter` times. Without a palette the hue sweeps round.
+fn draw_fractal<T>(buffer: &mut Buffer, zoom: T, time: T, max_iter: u32, palette: Option<&Palette>)
+where
+    T: Float + FromPrimitive,
+{
+    let num = |v: f64| T::from_f64(v).unwrap();
+    let (width, height) = (num(buffer.width as f64), num(buffer.height as f64));
+    let two = num(2.0);
+    let four = num(4.0);
+
+    for y in 0..buffer.height {
+        for x in 0..buffer.width {
+            // Map screen coordinates to the complex plane
+            let real = (num((x + 1) as f64) - width / two) * four / (width * zoom) + num(CENTER_REAL);
+            let imag = (num((y + 1) as f64) - height / two) * four / (height * zoom) + num(CENTER_IMAG);
+
+            let mut z_real = T::zero();
+            let mut z_imag = T::zero();
+            let mut iter = 0;
+
+            // Mandelbrot iteration
+            while iter < max_iter && z_real * z_real + z_imag * z_imag < four {
+                let new_real = z_real * z_real - z_imag * z_imag + real;
+                let new_imag = two * z_real * z_imag + imag;
+                z_real = new_real;
+                z_imag = new_imag;
+                iter += 1;
+            }
 
-                // Map screen coordinates to complex plane using f64
-                let real = (x as f64 - term.width as f64 / 2.0) * 4.0 / (term.width as f64 * zoom) + center_real;
-                let imag = (y as f64 - term.height as f64 / 2.0) * 4.0 / (term.height as f64 * zoom) + center_imag;
-
-                let mut z_real = 0.0;
-                let mut z_imag = 0.0;
-                let mut iter = 0;
-
-                // Mandelbrot iteration
-                while iter < max_iter && z_real * z_real + z_imag * z_imag < 4.0 {
-                    let new_real = z_real * z_real - z_imag * z_imag + real;
-                    let new_imag = 2.0 * z_real * z_imag + imag;
-                    z_real = new_real;
-                    z_imag = new_imag;
-                    iter += 1;
+            // Color based on iteration count
+            let color = if iter == max_iter {
+                color::Rgb(0, 0, 0)
+            } else {
+                // Create smooth coloring
+                let hue = (num(iter as f64 / max_iter as f64) + time * num(0.1)) % T::one();
+                match palette {
+                    Some(palette) => palette.sample_cyclic(hue.to_f32().unwrap_or(0.0)),
+                    None => {
+                        let (r, g, b) = hsv_to_rgb(hue, num(0.8), T::one());
+                        color::Rgb(r, g, b)
+                    }
                 }
+            };
 
-                // Color based on iteration count
-                if iter == max_iter {
-                    write!(
-                        stdout,
-                        "{}{}▓",
-                        cursor::Goto(x, y),
-                        color::Fg(color::Black)
-                    ).unwrap();
-                } else {
-                    // Create smooth coloring
-                    let hue = (iter as f64 / max_iter as f64 + time * 0.1) % 1.0;
-                    let saturation = 0.8;
-                    let value = if iter < max_iter { 1.0 } else { 0.0 };
-                    
-                    let (r, g, b) = hsv_to_rgb(hue, saturation, value);
-                    
-                    write!(
-                        stdout,
-                        "{}{}▓",
-                        cursor::Goto(x, y),
-                        color::Fg(color::Rgb(r, g, b))
-                    ).unwrap();
-                }
-            }
+            buffer.set(x, y, Cell { char: '▓', color, background: None });
         }
+    }
+}
+
+pub struct Mandelbrot {
+    max_iter: u32,
+    palette: Option<Palette>,
+}
+
+impl Mandelbrot {
+    const ZOOM_SPEED: f64 = 0.2;
+    const ZOOM_RESET_THRESHOLD: f64 = 50.0;
 
-        // Draw text
-        write!(
-            stdout,
-            "{}{}{}",
-            cursor::Goto(text_start_x, center_y),
-            color::Fg(color::White),
-            text
-        ).unwrap();
-        
-        stdout.flush().unwrap();
-        sleep(Duration::from_millis(32));
+    pub fn new() -> Self {
+        Self { max_iter: MAX_ITER, palette: None }
+    }
+
+    /// How far into the current zoom `elapsed` is, which starts over once
+    /// it passes the reset threshold.
+    fn time(elapsed: Duration) -> f64 {
+        let period = (Self::ZOOM_RESET_THRESHOLD.ln() - 0.05) / Self::ZOOM_SPEED;
+        (elapsed.as_secs_f64() * TIME_SCALE) % period
     }
 }
 
-pub fn mandelbrot_matrix_animation(text: &str, duration: Duration, term_signal: &Arc<AtomicBool>) {
-    let start = Instant::now();
-    let term = Terminal::new();
-    let mut stdout = stdout();
-    let (center_x, center_y) = term.center_pos();
-
-    // Calculate text boundaries
-    let text_start_x = center_x - (text.len() as u16 / 2);
-    let text_end_x = text_start_x + text.len() as u16;
-
-    write!(
-        stdout,
-        "{}{}{}",
-        termion::cursor::Hide,
-        clear::All,
-        termion::screen::ToAlternateScreen
-    ).unwrap();
-
-    let max_iter = 100;
-    let zoom_speed = 0.2;
-    let mut time = 0.0;
-    let center_real = -0.5;
-    let center_imag = 0.0;
-    let mut zooming_in = true;
-
-    while duration.as_millis() == 0 || start.elapsed() < duration {
-        if term_signal.load(Ordering::Relaxed) {
-            return;
-        }
+impl Default for Mandelbrot {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-        // Update time and handle zoom direction
-        time += if zooming_in { 0.016 } else { -0.016 }; // Time flows forward or backward
-        let zoom = (0.05_f64 + time * zoom_speed).exp();
+impl Animation for Mandelbrot {
+    fn render(&mut self, buffer: &mut Buffer, elapsed: Duration) {
+        let time = Self::time(elapsed);
+        let zoom = (0.05_f64 + time * Self::ZOOM_SPEED).exp();
 
-        // Switch direction when reaching extremes
-        if zooming_in && zoom > 2.5 {
-            zooming_in = false;
-        } else if !zooming_in && zoom < 0.2 {
-            zooming_in = true;
-        }
+        draw_fractal(buffer, zoom, time, self.max_iter, self.palette.as_ref());
+    }
 
-        // Rest of the code is identical to original mandelbrot_animation
-        for y in 1..=term.height {
-            for x in 1..=term.width {
-                if y == center_y && x >= text_start_x && x <= text_end_x {
-                    continue;
-                }
+    fn frame_interval(&self) -> Duration {
+        Duration::from_millis(32)
+    }
+
+    fn set_quality(&mut self, quality: f32) {
+        self.max_iter = max_iter(quality);
+    }
+
+    fn set_palette(&mut self, palette: &Palette) {
+        self.palette = Some(palette.clone());
+    }
+}
 
-                let real = (x as f64 - term.width as f64 / 2.0) * 4.0 / (term.width as f64 * zoom) + center_real;
-                let imag = (y as f64 - term.height as f64 / 2.0) * 4.0 / (term.height as f64 * zoom) + center_imag;
+pub struct MandelbrotMatrix {
+    max_iter: u32,
+    palette: Option<Palette>,
+}
 
-                let mut z_real = 0.0;
-                let mut z_imag = 0.0;
-                let mut iter = 0;
+impl MandelbrotMatrix {
+    const ZOOM_SPEED: f64 = 0.2;
+    const MIN_ZOOM: f64 = 0.2;
+    const MAX_ZOOM: f64 = 2.5;
 
-                while iter < max_iter && z_real * z_real + z_imag * z_imag < 4.0 {
-                    let new_real = z_real * z_real - z_imag * z_imag + real;
-                    let new_imag = 2.0 * z_real * z_imag + imag;
-                    z_real = new_real;
-                    z_imag = new_imag;
-                    iter += 1;
-                }
+    pub fn new() -> Self {
+        Self { max_iter: MAX_ITER, palette: None }
+    }
 
-                if iter == max_iter {
-                    write!(
-                        stdout,
-                        "{}{}▓",
-                        cursor::Goto(x, y),
-                        color::Fg(color::Black)
-                    ).unwrap();
-                } else {
-                    let hue = (iter as f64 / max_iter as f64 + time * 0.1) % 1.0;
-                    let saturation = 0.8;
-                    let value = if iter < max_iter { 1.0 } else { 0.0 };
-
-                    let (r, g, b) = hsv_to_rgb(hue, saturation, value);
-
-                    write!(
-                        stdout,
-                        "{}{}▓",
-                        cursor::Goto(x, y),
-                        color::Fg(color::Rgb(r, g, b))
-                    ).unwrap();
-                }
-            }
+    /// Where the zoom is after `elapsed`: time runs forward from 0 until the
+    /// zoom reaches its largest, then backward to its smallest, and so on.
+    fn time(elapsed: Duration) -> f64 {
+        let low = (Self::MIN_ZOOM.ln() - 0.05) / Self::ZOOM_SPEED;
+        let high = (Self::MAX_ZOOM.ln() - 0.05) / Self::ZOOM_SPEED;
+        let span = high - low;
+        let phase = (elapsed.as_secs_f64() * TIME_SCALE - low) % (2.0 * span);
+        if phase < span {
+            low + phase
+        } else {
+            high - (phase - span)
         }
+    }
+}
 
-        write!(
-            stdout,
-            "{}{}{}",
-            cursor::Goto(text_start_x, center_y),
-            color::Fg(color::White),
-            text
-        ).unwrap();
+impl Default for MandelbrotMatrix {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-        stdout.flush().unwrap();
-        sleep(Duration::from_millis(32));
+impl Animation for MandelbrotMatrix {
+    fn render(&mut self, buffer: &mut Buffer, elapsed: Duration) {
+        let time = Self::time(elapsed);
+        let zoom = (0.05_f64 + time * Self::ZOOM_SPEED).exp();
+
+        draw_fractal(buffer, zoom, time, self.max_iter, self.palette.as_ref());
+    }
+
+    fn frame_interval(&self) -> Duration {
+        Duration::from_millis(32)
+    }
+
+    fn set_quality(&mut self, quality: f32) {
+        self.max_iter = max_iter(quality);
+    }
+
+    fn set_palette(&mut self, palette: &Palette) {
+        self.palette = Some(palette.clone());
     }
 }
 
-pub fn mandelbrot_fast_animation(text: &str, duration: Duration, term_signal: &Arc<AtomicBool>) {
-    let start = Instant::now();
-    let term = Terminal::new();
-    let mut stdout = stdout();
-    let (center_x, center_y) = term.center_pos();
-
-    // Calculate text boundaries
-    let text_start_x = center_x - (text.len() as u16 / 2);
-    let text_end_x = text_start_x + text.len() as u16;
-
-    write!(
-        stdout,
-        "{}{}{}",
-        termion::cursor::Hide,
-        clear::All,
-        termion::screen::ToAlternateScreen
-    ).unwrap();
-
-    let max_iter = 100;
-    let mut zoom: f32;
-    let center_real = -0.5;
-    let center_imag = 0.0;
-
-    while duration.as_millis() == 0 || start.elapsed() < duration {
-        if term_signal.load(Ordering::Relaxed) {
-            return;
-        }
+pub struct MandelbrotFast {
+    max_iter: u32,
+    palette: Option<Palette>,
+}
 
-        let time = start.elapsed().as_secs_f32();
-        zoom = 1.0 + time.sin() * 0.5; // Zoom oscillates between 0.5 and 1.5
+impl MandelbrotFast {
+    pub fn new() -> Self {
+        Self { max_iter: MAX_ITER, palette: None }
+    }
+}
 
-        for y in 1..=term.height {
-            for x in 1..=term.width {
-                if y == center_y && x >= text_start_x && x <= text_end_x {
-                    continue;
-                }
+impl Default for MandelbrotFast {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-                // Map screen coordinates to complex plane
-                let real = (x as f32 - term.width as f32 / 2.0) * 4.0 / (term.width as f32 * zoom) + center_real;
-                let imag = (y as f32 - term.height as f32 / 2.0) * 4.0 / (term.height as f32 * zoom) + center_imag;
-
-                let mut z_real = 0.0;
-                let mut z_imag = 0.0;
-                let mut iter = 0;
-
-                // Mandelbrot iteration
-                while iter < max_iter && z_real * z_real + z_imag * z_imag < 4.0 {
-                    let new_real = z_real * z_real - z_imag * z_imag + real;
-                    let new_imag = 2.0 * z_real * z_imag + imag;
-                    z_real = new_real;
-                    z_imag = new_imag;
-                    iter += 1;
-                }
+impl Animation for MandelbrotFast {
+    fn render(&mut self, buffer: &mut Buffer, elapsed: Duration) {
+        let time = elapsed.as_secs_f32();
+        let zoom = 1.0 + time.sin() * 0.5; // Zoom oscillates between 0.5 and 1.5
 
-                // Color based on iteration count
-                if iter == max_iter {
-                    write!(
-                        stdout,
-                        "{}{}▓",
-                        cursor::Goto(x, y),
-                        color::Fg(color::Black)
-                    ).unwrap();
-                } else {
-                    // Create smooth coloring
-                    let hue = (iter as f32 / max_iter as f32 + time * 0.1) % 1.0;
-                    let saturation = 0.8;
-                    let value = if iter < max_iter { 1.0 } else { 0.0 };
-
-                    let (r, g, b) = hsv_to_rgb(hue, saturation, value);
-
-                    write!(
-                        stdout,
-                        "{}{}▓",
-                        cursor::Goto(x, y),
-                        color::Fg(color::Rgb(r, g, b))
-                    ).unwrap();
-                }
-            }
-        }
+        draw_fractal(buffer, zoom, time, self.max_iter, self.palette.as_ref());
+    }
+
+    fn frame_interval(&self) -> Duration {
+        Duration::from_millis(32)
+    }
+
+    fn set_quality(&mut self, quality: f32) {
+        self.max_iter = max_iter(quality);
+    }
 
-        // Draw text
-        write!(
-            stdout,
-            "{}{}{}",
-            cursor::Goto(text_start_x, center_y),
-            color::Fg(color::White),
-            text
-        ).unwrap();
-
-        stdout.flush().unwrap();
-        sleep(Duration::from_millis(32));
+    fn set_palette(&mut self, palette: &Palette) {
+        self.palette = Some(palette.clone());
     }
-}
\ No newline at end of file
+}
+
+pub fn mandelbrot_animation(text: &str, duration: Duration, controls: &Receiver<Control>) -> io::Result<()> {
+    play(&[Scene::new(AnimationStyle::Mandelbrot, text, duration)], &PlayOptions::default(), controls)
+}
+
+pub fn mandelbrot_matrix_animation(text: &str, duration: Duration, controls: &Receiver<Control>) -> io::Result<()> {
+    play(&[Scene::new(AnimationStyle::MandelbrotMatrix, text, duration)], &PlayOptions::default(), controls)
+}
+
+pub fn mandelbrot_fast_animation(text: &str, duration: Duration, controls: &Receiver<Control>) -> io::Result<()> {
+    play(&[Scene::new(AnimationStyle::MandelbrotFast, text, duration)], &PlayOptions::default(), controls)
+}
@@ -0,0 +1,213 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+use serde::Deserialize;
+use termion::color;
+
+use crate::animation::Animation;
+use crate::buffer::Buffer;
+use crate::color::{mix, relative_luminance, shade};
+use crate::palette::Palette;
+use crate::terminal::Cell;
+
+/// How much the picture may move, for viewers sensitive to motion or flashes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum ReducedMotion {
+    /// Play the styles as designed.
+    #[default]
+    Off,
+    /// Slow the styles down and limit how fast any cell can change brightness.
+    Limit,
+    /// Replace every style with a still gradient and fade the text in.
+    Static,
+}
+
+impl ReducedMotion {
+    /// How much slower the styles play with [`ReducedMotion::Limit`].
+    pub const SLOWDOWN: u32 = 4;
+}
+
+/// Parses `--reduced-motion` / `REVEAL_REDUCED_MOTION`: a mode name, or a
+/// yes/no value so `REVEAL_REDUCED_MOTION=1` works.
+impl FromStr for ReducedMotion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "" | "1" | "true" | "yes" | "on" | "limit" => Ok(ReducedMotion::Limit),
+            "static" => Ok(ReducedMotion::Static),
+            "0" | "false" | "no" | "off" => Ok(ReducedMotion::Off),
+            _ => Err(format!("invalid reduced motion mode '{}', expected limit, static or off", s)),
+        }
+    }
+}
+
+impl TryFrom<String> for ReducedMotion {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+/// WCAG 2.3.1 allows at most three flashes a second, a flash being a rise
+/// and fall in relative luminance of 10% or more. Letting luminance change by
+/// no more than 10% per sixth of a second keeps every cell under that.
+const MAX_LUMINANCE_RATE: f32 = 0.1 * 6.0;
+
+/// Keeps frames from changing faster than [`MAX_LUMINANCE_RATE`] allows,
+/// easing each cell from its last color towards the new one. Backgrounds
+/// are eased too, and a glyph that goes away fades into the background
+/// before it is cleared.
+pub struct Limiter {
+    shown: Buffer,
+}
+
+impl Limiter {
+    /// Starts from a black screen, so the first frames fade in.
+    pub fn new(width: u16, height: u16) -> Self {
+        Self { shown: Buffer::new(width, height) }
+    }
+
+    pub fn apply(&mut self, frame: &mut Buffer, dt: Duration) {
+        if (self.shown.width, self.shown.height) != (frame.width, frame.height) {
+            self.shown = Buffer::new(frame.width, frame.height);
+        }
+        let max_change = MAX_LUMINANCE_RATE * dt.as_secs_f32();
+
+        for y in 0..frame.height {
+            for x in 0..frame.width {
+                let (shown, cell) = (*self.shown.get(x, y), *frame.get(x, y));
+                let target = cell.background.unwrap_or(BLACK);
+                let eased = limit(shown.background.unwrap_or(BLACK), target, max_change);
+                let background = if eased == target { cell.background } else { Some(eased) };
+
+                let cell = if !is_blank(&cell) {
+                    // A glyph that just appeared starts out as its background.
+                    let from = if is_blank(&shown) { shown.background.unwrap_or(BLACK) } else { shown.color };
+                    Cell { color: limit(from, cell.color, max_change), background, ..cell }
+                } else if !is_blank(&shown) {
+                    let color = limit(shown.color, target, max_change);
+                    let char = if color == target { ' ' } else { shown.char };
+                    Cell { char, color, background }
+                } else {
+                    Cell { background, ..cell }
+                };
+                frame.set(x, y, cell);
+            }
+        }
+        self.shown = frame.clone();
+    }
+}
+
+const BLACK: color::Rgb = color::Rgb(0, 0, 0);
+
+/// Whether `cell` shows only its background.
+fn is_blank(cell: &Cell) -> bool {
+    cell.char == ' '
+}
+
+/// The color as far from `from` towards `to` as a luminance change of
+/// `max_change` allows.
+fn limit(from: color::Rgb, to: color::Rgb, max_change: f32) -> color::Rgb {
+    let start = relative_luminance(from);
+    if (relative_luminance(to) - start).abs() <= max_change {
+        return to;
+    }
+    // Luminance grows steadily along the blend, so search for how far to go.
+    let (mut low, mut high) = (0.0, 1.0);
+    for _ in 0..8 {
+        let mid = (low + high) / 2.0;
+        if (relative_luminance(mix(from, to, mid)) - start).abs() <= max_change {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    mix(from, to, low)
+}
+
+/// A still, dark gradient shown instead of every style with
+/// [`ReducedMotion::Static`]; the engine fades the text in over it. A
+/// palette is drawn darkened.
+#[derive(Default)]
+pub struct StaticGradient {
+    palette: Option<Palette>,
+}
+
+impl StaticGradient {
+    const FADE_IN: Duration = Duration::from_secs(2);
+
+    /// How bright the text is `elapsed` into the scene, from 0.0 to 1.0, as
+    /// it fades in.
+    pub fn text_brightness(elapsed: Duration) -> f32 {
+        let fade = (elapsed.as_secs_f32() / Self::FADE_IN.as_secs_f32()).min(1.0);
+        (60.0 + 195.0 * fade) / 255.0
+    }
+}
+
+impl Animation for StaticGradient {
+    fn render(&mut self, buffer: &mut Buffer, _elapsed: Duration) {
+        let height = buffer.height.max(2) as f32 - 1.0;
+        for y in 0..buffer.height {
+            let t = y as f32 / height;
+            let color = match &self.palette {
+                Some(palette) => shade(palette.sample(t), 0.3),
+                None => color::Rgb((20.0 + 20.0 * t) as u8, (30.0 + 10.0 * t) as u8, (70.0 - 20.0 * t) as u8),
+            };
+            for x in 0..buffer.width {
+                buffer.set(x, y, Cell { char: '█', color, background: None });
+            }
+        }
+    }
+
+    fn frame_interval(&self) -> Duration {
+        Duration::from_millis(50)
+    }
+
+    fn set_palette(&mut self, palette: &Palette) {
+        self.palette = Some(palette.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The color a cell puts on screen.
+    fn shown(cell: &Cell) -> color::Rgb {
+        if is_blank(cell) {
+            cell.background.unwrap_or(BLACK)
+        } else {
+            cell.color
+        }
+    }
+
+    #[test]
+    fn clearing_a_bright_frame_fades_it_out() {
+        let white = color::Rgb(255, 255, 255);
+        let mut bright = Buffer::new(4, 2);
+        for y in 0..2 {
+            bright.put_str(0, y, "█ █ ", white);
+            bright.set(1, y, Cell { char: ' ', color: white, background: Some(white) });
+        }
+        let mut limiter = Limiter { shown: bright.clone() };
+        let dt = Duration::from_millis(50);
+        let max_change = MAX_LUMINANCE_RATE * dt.as_secs_f32() + 0.005;
+
+        let mut previous = bright;
+        for _ in 0..40 {
+            let mut frame = Buffer::new(4, 2);
+            limiter.apply(&mut frame, dt);
+            for y in 0..2 {
+                for x in 0..4 {
+                    let change = relative_luminance(shown(frame.get(x, y))) - relative_luminance(shown(previous.get(x, y)));
+                    assert!(change.abs() <= max_change, "({}, {}) changed by {}", x, y, change);
+                }
+            }
+            previous = frame;
+        }
+        assert!(previous.cells().iter().all(|cell| *cell == Cell::default()), "never cleared");
+    }
+}
@@ -0,0 +1,99 @@
+use std::{
+    thread::sleep,
+    time::{Duration, Instant},
+};
+
+/// Lowest quality the pacer lowers detail to when frames take too long.
+const MIN_QUALITY: f32 = 0.2;
+
+/// Paces frames at a fixed rate: sleeps only for what rendering left of
+/// each frame's slot, skips the slots it fell behind on, and lowers the
+/// drawing quality while frames don't fit.
+pub struct Pacer {
+    interval: Duration,
+    /// When the next frame is due.
+    next: Instant,
+    quality: f32,
+    dropped: u64,
+}
+
+impl Pacer {
+    pub fn new(fps: u32) -> Self {
+        let interval = Duration::from_secs(1) / fps.max(1);
+        Self {
+            interval,
+            next: Instant::now() + interval,
+            quality: 1.0,
+            dropped: 0,
+        }
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Quality to draw the next frame at, from 0.0 to 1.0.
+    pub fn quality(&self) -> f32 {
+        self.quality
+    }
+
+    /// Frames skipped so far because rendering fell behind.
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    /// Adapts the quality to how long the last frame took to render: down
+    /// quickly when it didn't fit the interval, back up slowly when there's
+    /// plenty of room.
+    pub fn rendered(&mut self, render_time: Duration) {
+        let load = render_time.as_secs_f32() / self.interval.as_secs_f32();
+        if load > 0.9 {
+            self.quality = (self.quality * 0.8).max(MIN_QUALITY);
+        } else if load < 0.5 {
+            self.quality = (self.quality * 1.05).min(1.0);
+        }
+    }
+
+    /// Waits for the next frame's slot and returns how much time the frame
+    /// should advance the show by: one interval, or more when slots were
+    /// skipped, so the show still keeps to the wall clock.
+    pub fn wait(&mut self) -> Duration {
+        let now = Instant::now();
+        let mut step = self.interval;
+        if now > self.next {
+            let missed = ((now - self.next).as_nanos() / self.interval.as_nanos()) as u32;
+            if missed > 0 {
+                self.dropped += u64::from(missed);
+                self.next += self.interval * missed;
+                step += self.interval * missed;
+            }
+        } else {
+            sleep(self.next - now);
+        }
+        self.next += self.interval;
+        step
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_on_time_step_by_one_interval() {
+        let mut pacer = Pacer::new(100);
+        assert_eq!(pacer.wait(), pacer.interval());
+        assert_eq!(pacer.dropped(), 0);
+    }
+
+    #[test]
+    fn missed_slots_are_dropped_and_added_to_the_step() {
+        let mut pacer = Pacer::new(100);
+        sleep(Duration::from_millis(35));
+        let step = pacer.wait();
+        let slots = (step.as_nanos() / pacer.interval().as_nanos()) as u64;
+        assert_eq!(step, pacer.interval() * slots as u32);
+        assert!(slots >= 3, "{:?}", step);
+        assert_eq!(pacer.dropped(), slots - 1);
+    }
+}
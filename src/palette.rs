@@ -0,0 +1,120 @@
+use std::str::FromStr;
+
+use serde::Deserialize;
+use termion::color::Rgb;
+
+use crate::color::{mix_oklab, parse_hex};
+
+/// How many steps a gradient is cut into when a style cycles through it
+/// color by color.
+const GRADIENT_STEPS: usize = 12;
+
+/// The colors a style draws with, in place of its own.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum Palette {
+    /// A fixed set of colors, used as they are.
+    Discrete(Vec<Rgb>),
+    /// Evenly spaced color stops, blended in between in OKLab so the steps
+    /// look even.
+    Gradient(Vec<Rgb>),
+}
+
+impl Palette {
+    /// The built-in palettes' names, as taken by `--palette`.
+    pub const NAMES: [&'static str; 7] = ["viridis", "magma", "ocean", "sunset", "neon", "grayscale", "corporate"];
+
+    /// A built-in palette.
+    pub fn named(name: &str) -> Option<Palette> {
+        let hex = |colors: &[u32]| colors.iter().map(|&c| Rgb((c >> 16) as u8, (c >> 8) as u8, c as u8)).collect();
+        let palette = match name {
+            "viridis" => Palette::Gradient(hex(&[0x440154, 0x3b528b, 0x21918c, 0x5ec962, 0xfde725])),
+            "magma" => Palette::Gradient(hex(&[0x000004, 0x3b0f70, 0x8c2981, 0xde4968, 0xfe9f6d, 0xfcfdbf])),
+            "ocean" => Palette::Gradient(hex(&[0x001f3f, 0x0074d9, 0x39cccc, 0x7fdbff])),
+            "sunset" => Palette::Gradient(hex(&[0x3a1c71, 0xd76d77, 0xffaf7b, 0xffe29f])),
+            "neon" => Palette::Discrete(hex(&[0xff00ff, 0x00ffff, 0x39ff14, 0xffff00, 0xff3131])),
+            "grayscale" => Palette::Gradient(hex(&[0x111111, 0xffffff])),
+            "corporate" => Palette::Discrete(hex(&[
+                0x003f5c, 0x2f4b7c, 0x665191, 0xa05195, 0xd45087, 0xf95d6a, 0xff7c43, 0xffa600,
+            ])),
+            _ => return None,
+        };
+        Some(palette)
+    }
+
+    /// Parses `--gradient`: comma-separated `#rrggbb` stops.
+    pub fn parse_gradient(s: &str) -> Result<Palette, String> {
+        let stops = s.split(',').map(parse_hex).collect::<Result<Vec<_>, _>>()?;
+        if stops.len() < 2 {
+            return Err(format!("invalid gradient '{}', expected at least two colors like #ff0000,#0000ff", s));
+        }
+        Ok(Palette::Gradient(stops))
+    }
+
+    fn colors(&self) -> &[Rgb] {
+        match self {
+            Palette::Discrete(colors) | Palette::Gradient(colors) => colors,
+        }
+    }
+
+    /// The color at `t`, from 0.0 (first) to 1.0 (last). Gradients blend
+    /// between stops; discrete palettes pick the color `t` falls on.
+    pub fn sample(&self, t: f32) -> Rgb {
+        let colors = self.colors();
+        if colors.len() < 2 {
+            return colors.first().copied().unwrap_or(Rgb(255, 255, 255));
+        }
+        let t = if t.is_finite() { t.clamp(0.0, 1.0) } else { 0.0 };
+        match self {
+            Palette::Discrete(_) => colors[((t * colors.len() as f32) as usize).min(colors.len() - 1)],
+            Palette::Gradient(_) => {
+                let position = t * (colors.len() - 1) as f32;
+                let i = (position as usize).min(colors.len() - 2);
+                mix_oklab(colors[i], colors[i + 1], position - i as f32)
+            }
+        }
+    }
+
+    /// Like [`Palette::sample`], but for `t` that keeps growing, e.g. a hue
+    /// cycling with time: runs through the palette and back, so there is no
+    /// jump where it wraps around.
+    pub fn sample_cyclic(&self, t: f32) -> Rgb {
+        let t = t.rem_euclid(1.0);
+        self.sample(1.0 - (2.0 * t - 1.0).abs())
+    }
+
+    /// The `i`th color when stepping through the palette, wrapping around:
+    /// a discrete palette's colors in turn, or steps along a gradient and
+    /// back.
+    pub fn cycle(&self, i: usize) -> Rgb {
+        match self {
+            Palette::Discrete(colors) if !colors.is_empty() => colors[i % colors.len()],
+            Palette::Discrete(_) => self.sample(0.0),
+            Palette::Gradient(_) => self.sample_cyclic((i % GRADIENT_STEPS) as f32 / GRADIENT_STEPS as f32),
+        }
+    }
+}
+
+/// Parses `--palette`: the name of a built-in palette.
+impl FromStr for Palette {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Palette::named(&s.trim().to_ascii_lowercase())
+            .ok_or_else(|| format!("unknown palette '{}' (available: {})", s, Palette::NAMES.join(", ")))
+    }
+}
+
+/// `palette = "..."` in the config or a show file: a built-in palette's name,
+/// or gradient stops as `--gradient` takes them.
+impl TryFrom<String> for Palette {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        if s.contains(',') {
+            Palette::parse_gradient(&s)
+        } else {
+            s.parse()
+        }
+    }
+}
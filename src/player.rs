@@ -0,0 +1,362 @@
+use std::{
+    io::{self, stdout, Write},
+    thread::sleep,
+    time::{Duration, Instant},
+};
+use termion::{clear, raw::IntoRawMode};
+use std::sync::mpsc::Receiver;
+
+use crate::bandwidth::Throttle;
+use crate::buffer::Buffer;
+use crate::cli::AnimationStyle;
+use crate::control::Control;
+use crate::engine::{ProgressStyle, Reveal};
+use crate::motion::ReducedMotion;
+use crate::palette::Palette;
+use crate::pacing::Pacer;
+use crate::renderer::Renderer;
+use crate::stats::{CountingWriter, Frame, Stats};
+use crate::terminal::Rect;
+use crate::layout::{Placement, TextBlock};
+use crate::legibility::{TextColor, TextEffect};
+use crate::theme::Theme;
+use crate::transition::Transition;
+
+/// How long a [`Control::Trigger`] plays.
+const TRIGGER_DURATION: Duration = Duration::from_millis(1500);
+
+/// A style played for a fixed time with its own text.
+#[derive(Clone, Debug)]
+pub struct Scene {
+    pub style: AnimationStyle,
+    pub text: String,
+    pub duration: Duration,
+    /// How to blend in from the previous scene, if any.
+    pub transition: Option<Transition>,
+    /// Colors this scene draws with instead of the show's.
+    pub palette: Option<Palette>,
+    pub text_color: Option<TextColor>,
+    pub text_effect: Option<TextEffect>,
+    /// Overrides [`PlayOptions::big_text`] for this scene.
+    pub big_text: Option<bool>,
+}
+
+impl Scene {
+    pub fn new(style: AnimationStyle, text: &str, duration: Duration) -> Self {
+        Self {
+            style,
+            text: text.to_string(),
+            duration,
+            transition: None,
+            palette: None,
+            text_color: None,
+            text_effect: None,
+            big_text: None,
+        }
+    }
+}
+
+/// How a list of scenes is played.
+#[derive(Clone, Debug)]
+pub struct PlayOptions {
+    /// Times to play the scenes (forever if 0).
+    pub loops: u32,
+    /// Animate in this many lines below the cursor instead of full screen.
+    pub inline_height: Option<u16>,
+    /// Play inside this region of the screen rather than all of it.
+    pub viewport: Option<Rect>,
+    /// Frame the viewport with a border, titled if a title is given.
+    pub border: bool,
+    pub title: Option<String>,
+    /// Played on [`Control::Finish`]; without one, finishing stops right away.
+    pub outro: Option<Scene>,
+    pub progress_style: ProgressStyle,
+    /// Draw the text in large block glyphs, e.g. for a countdown.
+    pub big_text: bool,
+    /// Pick each next scene at random instead of in order.
+    pub shuffle: bool,
+    /// Fade the picture down over this long, against burn-in.
+    pub dim: Option<Duration>,
+    pub reduced_motion: ReducedMotion,
+    /// Colors every style draws with instead of its own.
+    pub palette: Option<Palette>,
+    /// Brand colors for the text, accents and, without a palette, the styles.
+    pub theme: Theme,
+    /// Overrides the theme's text color.
+    pub text_color: Option<TextColor>,
+    /// Defaults to a box when the theme has a text background.
+    pub text_effect: Option<TextEffect>,
+    /// Where the scenes' text goes.
+    pub placement: Placement,
+    /// Text placed on its own besides the scenes'.
+    pub blocks: Vec<TextBlock>,
+    /// Hold this frame rate, dropping frames and detail when behind, instead
+    /// of each style's own rate.
+    pub fps: Option<u32>,
+    /// Overlay frame rate and costs, and report them on stderr at the end.
+    pub stats: bool,
+    /// Keep output under this many bytes per second, redrawing cells less
+    /// precisely when over.
+    pub max_bandwidth: Option<u64>,
+    /// Seed all randomness and step time by whole frames, so runs with the
+    /// same seed and size write the same bytes.
+    pub seed: Option<u64>,
+}
+
+impl Default for PlayOptions {
+    fn default() -> Self {
+        Self {
+            loops: 1,
+            inline_height: None,
+            viewport: None,
+            border: false,
+            title: None,
+            outro: None,
+            progress_style: ProgressStyle::Bar,
+            big_text: false,
+            shuffle: false,
+            dim: None,
+            reduced_motion: ReducedMotion::Off,
+            palette: None,
+            theme: Theme::default(),
+            text_color: None,
+            text_effect: None,
+            placement: Placement::default(),
+            blocks: Vec::new(),
+            fps: None,
+            stats: false,
+            max_bandwidth: None,
+            seed: None,
+        }
+    }
+}
+
+/// The whole output area, with the animation placed in its viewport.
+struct Stage {
+    screen: Buffer,
+    content: Rect,
+}
+
+impl Stage {
+    fn new(screen: Rect, options: &PlayOptions) -> Self {
+        let region = options.viewport.map_or(screen, |viewport| viewport.intersect(&screen));
+        let mut stage = Stage {
+            screen: Buffer::new(screen.width, screen.height),
+            content: region,
+        };
+        if options.border || options.title.is_some() {
+            match options.theme.accent {
+                Some(accent) => stage.screen.draw_border_in(region, options.title.as_deref(), accent),
+                None => stage.screen.draw_border(region, options.title.as_deref()),
+            }
+            stage.content = region.inset(1);
+        }
+        stage
+    }
+
+    /// Draws `frame` with the stats overlay on top, if any, returning how
+    /// many cells changed.
+    fn present<W: Write>(
+        &mut self,
+        renderer: &mut Renderer,
+        out: &mut W,
+        frame: &Buffer,
+        stats: Option<&Stats>,
+    ) -> io::Result<usize> {
+        self.screen.blit(frame, self.content.x, self.content.y);
+        if let Some(stats) = stats {
+            stats.draw_overlay(&mut self.screen, self.content);
+        }
+        renderer.draw(out, &self.screen)
+    }
+}
+
+/// Plays `scenes` in order until they are done or a [`Control`] stops them,
+/// taking over the terminal and restoring it afterwards. Fails when stdout
+/// is not a terminal.
+pub fn play(scenes: &[Scene], options: &PlayOptions, controls: &Receiver<Control>) -> io::Result<()> {
+    if !termion::is_tty(&stdout()) {
+        return Err(io::Error::other("stdout is not a terminal"));
+    }
+    let size = termion::terminal_size()?;
+    let _raw = stdout().into_raw_mode()?;
+    play_to(&mut stdout(), size, scenes, options, controls)
+}
+
+/// Plays `scenes` to any terminal behind `out`, e.g. a network client, that
+/// is `size` cells large. [`Control::Resize`] reports a new size.
+pub fn play_to<W: Write>(
+    out: &mut W,
+    size: (u16, u16),
+    scenes: &[Scene],
+    options: &PlayOptions,
+    controls: &Receiver<Control>,
+) -> io::Result<()> {
+    let mut renderer = Renderer::new();
+    let shown = show(out, size, scenes, options, controls, &mut renderer);
+
+    // Restore the terminal however the show ended; inline output stays in
+    // the scrollback.
+    let restored = restore(out, &mut renderer, options);
+    let (stats, dropped) = shown?;
+    restored?;
+
+    if let Some(stats) = &stats {
+        stats.report(&mut io::stderr(), dropped)?;
+    }
+    Ok(())
+}
+
+/// Takes over `out` and plays the show, returning its stats, if kept, and
+/// the frames the pacer dropped. Leaves restoring the terminal to the caller.
+fn show<W: Write>(
+    out: &mut W,
+    size: (u16, u16),
+    scenes: &[Scene],
+    options: &PlayOptions,
+    controls: &Receiver<Control>,
+    renderer: &mut Renderer,
+) -> io::Result<(Option<Stats>, Option<u64>)> {
+    let (width, height) = size;
+
+    // Setup terminal
+    let height = match options.inline_height {
+        Some(lines) => {
+            let lines = lines.min(height);
+            write!(out, "{}", termion::cursor::Hide)?;
+            *renderer = Renderer::inline(out, lines)?;
+            lines
+        }
+        None => {
+            write!(
+                out,
+                "{}{}{}",
+                termion::cursor::Hide,
+                clear::All,
+                termion::screen::ToAlternateScreen
+            )?;
+            height
+        }
+    };
+    let mut stage = Stage::new(Rect::new(0, 0, width, height), options);
+    let mut builder = Reveal::builder()
+        .scenes(scenes.iter().cloned())
+        .loops(options.loops)
+        .progress_style(options.progress_style)
+        .big_text(options.big_text)
+        .shuffle(options.shuffle)
+        .dim(options.dim)
+        .reduced_motion(options.reduced_motion)
+        .size(stage.content.width, stage.content.height);
+    if let Some(palette) = &options.palette {
+        builder = builder.palette(palette.clone());
+    }
+    builder = builder.theme(options.theme.clone());
+    if let Some(color) = options.text_color {
+        builder = builder.text_color(color);
+    }
+    if let Some(effect) = options.text_effect {
+        builder = builder.text_effect(effect);
+    }
+    builder = builder.placement(options.placement);
+    for block in &options.blocks {
+        builder = builder.block(block.clone());
+    }
+    if let Some(seed) = options.seed {
+        builder = builder.seed(seed);
+    }
+    let mut reveal = builder.build();
+
+    let mut pacer = options.fps.map(Pacer::new);
+    let mut stats = options.stats.then(|| Stats::new(reveal.seed()));
+    let mut throttle = options.max_bandwidth.map(Throttle::new);
+    let mut last = Instant::now();
+    let mut dt = Duration::ZERO;
+    'show: loop {
+        for control in controls.try_iter() {
+            match control {
+                Control::SetText(text) => reveal.set_text(Some(&text)),
+                Control::SetProgress(progress) => reveal.set_progress(Some(progress)),
+                Control::SetStyle(style) => reveal.set_style(style),
+                Control::Trigger(style) => reveal.trigger(style, TRIGGER_DURATION),
+                Control::Pause => reveal.set_paused(true),
+                Control::Resume => reveal.set_paused(false),
+                // Inline output keeps the lines it started with.
+                Control::Resize(width, height) if options.inline_height.is_none() => {
+                    stage = Stage::new(Rect::new(0, 0, width, height), options);
+                    reveal.resize(stage.content.width, stage.content.height);
+                    *renderer = Renderer::new();
+                    write!(out, "{}", clear::All)?;
+                }
+                Control::Resize(..) => {}
+                Control::Finish => match &options.outro {
+                    Some(outro) => reveal.outro(outro.clone()),
+                    None => break 'show,
+                },
+                Control::Cancel => break 'show,
+            }
+        }
+
+        let started = Instant::now();
+        reveal.tick(dt);
+        if reveal.is_finished() {
+            break;
+        }
+
+        let mut counted = CountingWriter::new(out);
+        let cells_changed = stage.present(renderer, &mut counted, reveal.buffer(), stats.as_ref())?;
+        if let Some(throttle) = &mut throttle {
+            throttle.spend(counted.written, dt);
+            renderer.set_granularity(throttle.tolerance(), throttle.interlace());
+        }
+        if let Some(stats) = &mut stats {
+            stats.record(Frame {
+                render_time: started.elapsed(),
+                bytes: counted.written,
+                cells_changed,
+            });
+        }
+        // A seeded run doesn't depend on how fast this machine is: time moves
+        // on by exactly one frame each frame, and quality stays put.
+        dt = match &mut pacer {
+            Some(pacer) if options.seed.is_some() => {
+                pacer.wait();
+                pacer.interval()
+            }
+            None if options.seed.is_some() => {
+                sleep(reveal.frame_interval());
+                reveal.frame_interval()
+            }
+            Some(pacer) => {
+                pacer.rendered(started.elapsed());
+                reveal.set_quality(pacer.quality());
+                pacer.wait()
+            }
+            None => {
+                sleep(reveal.frame_interval());
+                let now = Instant::now();
+                let dt = now - last;
+                last = now;
+                dt
+            }
+        };
+    }
+
+    Ok((stats, pacer.as_ref().map(Pacer::dropped)))
+}
+
+fn restore<W: Write>(out: &mut W, renderer: &mut Renderer, options: &PlayOptions) -> io::Result<()> {
+    renderer.finish(out)?;
+    if options.inline_height.is_some() {
+        write!(out, "{}", termion::cursor::Show)?;
+    } else {
+        write!(
+            out,
+            "{}{}{}",
+            termion::screen::ToMainScreen,
+            termion::cursor::Show,
+            clear::All
+        )?;
+    }
+    out.flush()
+}
@@ -0,0 +1,79 @@
+use std::{
+    io::{self, BufRead, Write},
+    sync::mpsc::Sender,
+    thread,
+};
+
+use crate::control::Control;
+use crate::text::normalize;
+
+/// One line of progress input: `42`, `42%` or `42 Compiling foo`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Update {
+    /// Percentage from 0 to 100, if the line started with one.
+    pub percent: Option<f32>,
+    pub message: String,
+}
+
+impl Update {
+    /// Reads a line; one that doesn't start with a number is all message.
+    pub fn parse(line: &str) -> Self {
+        let line = line.trim();
+        let (first, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        match first.trim_end_matches('%').parse::<f32>() {
+            Ok(percent) if percent.is_finite() => Update {
+                percent: Some(percent.clamp(0.0, 100.0)),
+                message: normalize(rest),
+            },
+            _ => Update {
+                percent: None,
+                message: normalize(line),
+            },
+        }
+    }
+}
+
+/// Sends progress read from stdin to the player, finishing once 100 is
+/// reached. If stdin ends before that, the player is cancelled.
+pub fn follow_stdin(controls: Sender<Control>) {
+    thread::spawn(move || {
+        let mut done = false;
+        for line in io::stdin().lock().lines() {
+            let Ok(line) = line else { break };
+            let update = Update::parse(&line);
+            let mut sent = Ok(());
+            if !update.message.is_empty() {
+                sent = sent.and(controls.send(Control::SetText(update.message)));
+            }
+            if let Some(percent) = update.percent {
+                sent = sent.and(controls.send(Control::SetProgress(percent / 100.0)));
+                done = percent >= 100.0;
+            }
+            if sent.is_err() || done {
+                break;
+            }
+        }
+        let _ = controls.send(if done { Control::Finish } else { Control::Cancel });
+    });
+}
+
+/// Echoes progress read from stdin as plain lines like `42% Compiling foo`,
+/// for when there is no terminal to animate. Stops once 100 is reached.
+pub fn print_stdin<W: Write>(out: &mut W) -> io::Result<()> {
+    for line in io::stdin().lock().lines() {
+        let update = Update::parse(&line?);
+        let line = match update.percent {
+            Some(percent) => format!("{}% {}", percent, update.message),
+            None => update.message,
+        };
+        if line.is_empty() {
+            continue;
+        }
+        writeln!(out, "{}", line.trim_end())?;
+        out.flush()?;
+        if update.percent.is_some_and(|percent| percent >= 100.0) {
+            break;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,122 @@
+use std::{
+    fs,
+    io::{self, BufRead, BufReader, Write},
+    os::unix::net::{UnixListener, UnixStream},
+    path::{Path, PathBuf},
+    sync::mpsc::Sender,
+    thread,
+};
+
+use clap::Subcommand;
+use serde::{Deserialize, Serialize};
+
+use crate::cli::AnimationStyle;
+use crate::control::Control;
+
+/// A command sent over the socket, one JSON object per line, e.g.
+/// `{"command": "set_text", "text": "Hello"}`.
+#[derive(Subcommand, Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum Request {
+    /// Replace the text
+    SetText { text: String },
+    /// Switch to another style
+    SetStyle {
+        #[arg(value_enum)]
+        style: AnimationStyle,
+    },
+    /// Freeze the animation
+    Pause,
+    /// Carry on after a pause
+    Resume,
+    /// Stop playing
+    Quit,
+    /// Play an explosion over the animation
+    TriggerExplosion,
+}
+
+impl Request {
+    pub fn control(self) -> Control {
+        match self {
+            Request::SetText { text } => Control::SetText(text),
+            Request::SetStyle { style } => Control::SetStyle(style),
+            Request::Pause => Control::Pause,
+            Request::Resume => Control::Resume,
+            Request::Quit => Control::Cancel,
+            Request::TriggerExplosion => Control::Trigger(AnimationStyle::Explosion),
+        }
+    }
+}
+
+/// The answer to every request line.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Reply {
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// A socket accepting [`Request`]s; the socket file is removed on drop.
+pub struct Listener {
+    path: PathBuf,
+}
+
+impl Drop for Listener {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Listens on `path`, passing every request on to the player. A socket
+/// left behind by an earlier run is replaced, but not one still in use.
+pub fn listen(path: &Path, controls: Sender<Control>) -> io::Result<Listener> {
+    if path.exists() {
+        if UnixStream::connect(path).is_ok() {
+            return Err(io::Error::new(io::ErrorKind::AddrInUse, "another reveal is listening there"));
+        }
+        fs::remove_file(path)?;
+    }
+    let listener = UnixListener::bind(path)?;
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(stream) = stream else { continue };
+            let controls = controls.clone();
+            thread::spawn(move || serve(stream, controls));
+        }
+    });
+    Ok(Listener { path: path.to_path_buf() })
+}
+
+fn serve(stream: UnixStream, controls: Sender<Control>) -> io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let reply = match serde_json::from_str::<Request>(&line) {
+            Ok(request) => match controls.send(request.control()) {
+                Ok(()) => Reply { ok: true, error: None },
+                Err(_) => Reply { ok: false, error: Some("reveal is shutting down".to_string()) },
+            },
+            Err(err) => Reply { ok: false, error: Some(err.to_string()) },
+        };
+        writeln!(writer, "{}", serde_json::to_string(&reply)?)?;
+    }
+    Ok(())
+}
+
+/// Sends `request` to the reveal listening on `path` and waits for the reply.
+pub fn send(path: &Path, request: &Request) -> io::Result<()> {
+    let mut stream = UnixStream::connect(path)?;
+    writeln!(stream, "{}", serde_json::to_string(request)?)?;
+
+    let mut line = String::new();
+    BufReader::new(stream).read_line(&mut line)?;
+    let reply: Reply = serde_json::from_str(&line)?;
+    match reply.error {
+        Some(error) if !reply.ok => Err(io::Error::other(error)),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,258 @@
+use std::io::{self, Write};
+
+use termion::{color, cursor};
+
+use crate::buffer::Buffer;
+use crate::terminal::Cell;
+
+/// Begin / end synchronized output (DEC mode 2026): the terminal holds the
+/// frame back until it is complete, so it never shows half of one. Terminals
+/// without it ignore the sequences.
+const BEGIN_SYNC: &str = "\x1b[?2026h";
+const END_SYNC: &str = "\x1b[?2026l";
+
+/// How the renderer addresses cells on screen.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+enum Positioning {
+    /// Moves anywhere on a full-screen buffer.
+    #[default]
+    Absolute,
+    /// Relative moves only, inside a region that starts at the cursor's row,
+    /// so the animation can live inline in the scrollback.
+    Relative,
+}
+
+/// Writes buffers to a terminal, only touching cells that changed since the
+/// previous frame, with as few bytes as it can: the cursor moves relative to
+/// where it is, and colors are only sent when they change.
+#[derive(Default)]
+pub struct Renderer {
+    /// What is on screen now.
+    prev: Option<Buffer>,
+    positioning: Positioning,
+    /// Where the cursor is, if known. A column equal to the width means a
+    /// line wrap is pending.
+    cursor: Option<(u16, u16)>,
+    /// The foreground color last sent, if known.
+    color: Option<color::Rgb>,
+    /// The background last sent, if known; `Some(None)` is the default.
+    background: Option<Option<color::Rgb>>,
+    /// Cells whose character is the same and whose color is within this much
+    /// per channel of what's shown are left alone.
+    tolerance: u8,
+    /// Update only every this many rows per frame, taking turns.
+    interlace: u16,
+    frames: u16,
+}
+
+impl Renderer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A renderer for `--inline`, drawing into `height` lines reserved below
+    /// the cursor instead of taking over the screen.
+    pub fn inline<W: Write>(out: &mut W, height: u16) -> io::Result<Self> {
+        // Scroll enough blank lines into view, then go back to the first one.
+        for _ in 0..height {
+            write!(out, "\r\n")?;
+        }
+        if height > 0 {
+            write!(out, "{}", cursor::Up(height))?;
+        }
+        write!(out, "\r")?;
+
+        Ok(Self {
+            positioning: Positioning::Relative,
+            cursor: Some((0, 0)),
+            ..Self::default()
+        })
+    }
+
+    /// Trades accuracy for fewer bytes: colors may drift `tolerance` per
+    /// channel from what's shown before a cell is redrawn, and each frame
+    /// only updates one in `interlace` rows. `(0, 1)` draws every change.
+    pub fn set_granularity(&mut self, tolerance: u8, interlace: u16) {
+        self.tolerance = tolerance;
+        self.interlace = interlace.max(1);
+    }
+
+    /// Draws `buffer`, returning how many cells had to be written.
+    pub fn draw<W: Write>(&mut self, out: &mut W, buffer: &Buffer) -> io::Result<usize> {
+        // A size change means nothing on screen can be trusted anymore.
+        let mut shown = match self.prev.take() {
+            Some(prev) if prev.width == buffer.width && prev.height == buffer.height => Some(prev),
+            _ => None,
+        };
+        let mut frame = Vec::new();
+        let mut changed = 0;
+        let interlace = self.interlace.max(1);
+        let phase = self.frames % interlace;
+        self.frames = self.frames.wrapping_add(1);
+
+        for y in 0..buffer.height {
+            if shown.is_some() && y % interlace != phase {
+                continue;
+            }
+            for x in 0..buffer.width {
+                let cell = buffer.get(x, y);
+                if shown.as_ref().is_some_and(|shown| self.looks_same(shown.get(x, y), cell)) {
+                    continue;
+                }
+                self.move_to(&mut frame, x, y, buffer.width)?;
+                if self.color != Some(cell.color) {
+                    write!(frame, "{}", color::Fg(cell.color))?;
+                    self.color = Some(cell.color);
+                }
+                if self.background != Some(cell.background) {
+                    match cell.background {
+                        Some(background) => write!(frame, "{}", color::Bg(background))?,
+                        None => write!(frame, "{}", color::Bg(color::Reset))?,
+                    }
+                    self.background = Some(cell.background);
+                }
+                write!(frame, "{}", cell.char)?;
+                self.cursor = Some((x + 1, y));
+                if self.positioning == Positioning::Absolute && !is_narrow(cell.char) {
+                    // It may have taken two columns; go by coordinates next.
+                    self.cursor = None;
+                }
+                if let Some(shown) = &mut shown {
+                    shown.set(x, y, *cell);
+                }
+                changed += 1;
+            }
+        }
+        if !frame.is_empty() {
+            out.write_all(BEGIN_SYNC.as_bytes())?;
+            out.write_all(&frame)?;
+            out.write_all(END_SYNC.as_bytes())?;
+            out.flush()?;
+        }
+
+        self.prev = Some(shown.unwrap_or_else(|| buffer.clone()));
+        Ok(changed)
+    }
+
+    /// Puts the terminal's own colors back, and leaves the cursor on the line
+    /// below an inline region so the last frame stays in the scrollback.
+    pub fn finish<W: Write>(&mut self, out: &mut W) -> io::Result<()> {
+        write!(out, "{}{}", color::Fg(color::Reset), color::Bg(color::Reset))?;
+        (self.color, self.background) = (None, Some(None));
+        if self.positioning == Positioning::Relative {
+            let (width, height) = self.prev.as_ref().map_or((0, 0), |prev| (prev.width, prev.height));
+            self.move_to(out, 0, height.saturating_sub(1), width)?;
+            write!(out, "\r\n")?;
+        }
+        out.flush()
+    }
+
+    fn looks_same(&self, shown: &Cell, cell: &Cell) -> bool {
+        let near = |a: u8, b: u8| a.abs_diff(b) <= self.tolerance;
+        shown.char == cell.char
+            && shown.background == cell.background
+            && near(shown.color.0, cell.color.0)
+            && near(shown.color.1, cell.color.1)
+            && near(shown.color.2, cell.color.2)
+    }
+
+    /// Moves the cursor to `(x, y)` with the shortest sequence at hand.
+    fn move_to<W: Write>(&mut self, out: &mut W, x: u16, y: u16, width: u16) -> io::Result<()> {
+        let Some((cur_x, cur_y)) = self.cursor else {
+            // Only full-screen output can lose track of the cursor.
+            self.cursor = Some((x, y));
+            return write!(out, "{}", cursor::Goto(x + 1, y + 1));
+        };
+        self.cursor = Some((x, y));
+        if (x, y) == (cur_x, cur_y) {
+            return Ok(());
+        }
+        // Moving right along the row, unless a wrap is pending.
+        if y == cur_y && x > cur_x && cur_x < width {
+            return write!(out, "{}", cursor::Right(x - cur_x));
+        }
+        if self.positioning == Positioning::Absolute {
+            if x == 0 && y == cur_y + 1 {
+                return write!(out, "\r\n");
+            }
+            return write!(out, "{}", cursor::Goto(x + 1, y + 1));
+        }
+
+        if y > cur_y {
+            write!(out, "{}", cursor::Down(y - cur_y))?;
+        } else if y < cur_y {
+            write!(out, "{}", cursor::Up(cur_y - y))?;
+        }
+        // `\r` also resolves a pending wrap without scrolling.
+        write!(out, "\r")?;
+        if x > 0 {
+            write!(out, "{}", cursor::Right(x))?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether `c` surely takes a single column: ASCII, and the box drawing and
+/// block characters the styles draw with.
+fn is_narrow(c: char) -> bool {
+    c.is_ascii() || ('\u{2500}'..='\u{259F}').contains(&c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WHITE: color::Rgb = color::Rgb(255, 255, 255);
+
+    fn buffer(rows: &[&str]) -> Buffer {
+        let mut buffer = Buffer::new(rows[0].len() as u16, rows.len() as u16);
+        for (y, row) in rows.iter().enumerate() {
+            buffer.put_str(0, y as u16, row, WHITE);
+        }
+        buffer
+    }
+
+    fn draw(renderer: &mut Renderer, buffer: &Buffer) -> String {
+        let mut out = Vec::new();
+        renderer.draw(&mut out, buffer).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn inline_rows_resolve_the_pending_wrap_with_a_carriage_return() {
+        let mut renderer = Renderer::inline(&mut Vec::new(), 2).unwrap();
+        let expected = format!(
+            "{}{}{}abc{}\rdef{}",
+            BEGIN_SYNC,
+            color::Fg(WHITE),
+            color::Bg(color::Reset),
+            cursor::Down(1),
+            END_SYNC
+        );
+        assert_eq!(draw(&mut renderer, &buffer(&["abc", "def"])), expected);
+    }
+
+    #[test]
+    fn full_screen_rows_follow_on_with_a_newline() {
+        let mut renderer = Renderer::new();
+        let output = draw(&mut renderer, &buffer(&["abc", "def"]));
+        assert!(output.contains("abc\r\ndef"), "{:?}", output);
+        // The last column leaves a wrap pending, so the next cell on that
+        // row is reached by its coordinates rather than a move right.
+        let output = draw(&mut renderer, &buffer(&["abx", "def"]));
+        assert!(output.contains(&format!("{}x", cursor::Goto(3, 1))), "{:?}", output);
+    }
+
+    #[test]
+    fn interlace_takes_turns_on_rows() {
+        let mut renderer = Renderer::new();
+        draw(&mut renderer, &buffer(&["a", "a", "a", "a"]));
+        renderer.set_granularity(0, 2);
+        let changed = buffer(&["b", "b", "b", "b"]);
+        let mut counts = Vec::new();
+        for _ in 0..3 {
+            counts.push(renderer.draw(&mut Vec::new(), &changed).unwrap());
+        }
+        assert_eq!(counts, [2, 2, 0]);
+    }
+}
@@ -0,0 +1,66 @@
+use std::{
+    fs, io,
+    path::Path,
+    sync::mpsc::{Receiver, RecvTimeoutError},
+    time::Duration,
+};
+
+use termion::event::Key;
+
+use crate::control::start;
+use crate::player::{PlayOptions, Scene};
+use crate::text::normalize;
+
+/// Reads one message per line, skipping blank lines.
+pub fn load_messages(path: &Path) -> io::Result<Vec<String>> {
+    let messages: Vec<String> = fs::read_to_string(path)?
+        .lines()
+        .map(normalize)
+        .filter(|line| !line.is_empty())
+        .collect();
+    if messages.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "no messages in file"));
+    }
+    Ok(messages)
+}
+
+/// Plays `scenes` until a key comes in on `keys`, showing each of `messages`
+/// in turn for as long as a scene lasts. With an `idle` time, waits that long
+/// without a keypress first, and goes back to waiting after every stop.
+/// Returns on Ctrl-C, or after the first stop without an `idle` time.
+pub fn run(scenes: &[Scene], options: &PlayOptions, messages: &[String], idle: Option<Duration>, keys: &Receiver<Key>) {
+    let interval = scenes.first().map_or(Duration::ZERO, |scene| scene.duration);
+    loop {
+        if let Some(idle) = idle {
+            loop {
+                match keys.recv_timeout(idle) {
+                    Ok(Key::Ctrl('c')) | Err(RecvTimeoutError::Disconnected) => return,
+                    Ok(_) => {}
+                    Err(RecvTimeoutError::Timeout) => break,
+                }
+            }
+        }
+
+        let Ok(saver) = start(scenes.to_vec(), options.clone(), false) else { return };
+        let mut messages = messages.iter().cycle();
+        loop {
+            if let Some(message) = messages.next() {
+                saver.update_text(message);
+            }
+            let key = if interval.is_zero() {
+                keys.recv().map_err(|_| RecvTimeoutError::Disconnected)
+            } else {
+                keys.recv_timeout(interval)
+            };
+            match key {
+                Ok(Key::Ctrl('c')) | Err(RecvTimeoutError::Disconnected) => return saver.cancel(),
+                Ok(_) => break,
+                Err(RecvTimeoutError::Timeout) => {}
+            }
+        }
+        saver.cancel();
+        if idle.is_none() {
+            return;
+        }
+    }
+}
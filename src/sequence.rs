@@ -0,0 +1,282 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+use clap::ValueEnum;
+use serde::Deserialize;
+
+use crate::cli::AnimationStyle;
+use crate::legibility::{TextColor, TextEffect};
+use crate::palette::Palette;
+use crate::player::Scene;
+use crate::transition::{Transition, TransitionKind};
+
+pub const DEFAULT_DURATION: u64 = 5000;
+pub const DEFAULT_TRANSITION_DURATION: u64 = 500;
+
+/// One segment of a `--sequence` playlist.
+///
+/// Segments are written as `STYLE[:DURATION][:KEY=VALUE...]`, e.g.
+/// `waves:2000:text=Hello:transition=crossfade`. Fields left out fall back
+/// to the top-level `--text` / `--duration` / `--transition` values.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Segment {
+    pub style: AnimationStyle,
+    pub duration: Option<u64>,
+    pub text: Option<String>,
+    /// Transition into this segment from the one before it.
+    pub transition: Option<TransitionKind>,
+    pub transition_duration: Option<u64>,
+    pub palette: Option<Palette>,
+    pub text_color: Option<TextColor>,
+    pub text_effect: Option<TextEffect>,
+    pub big_text: Option<bool>,
+}
+
+/// Scene fields that can be set once for every segment.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct SceneDefaults {
+    pub text: Option<String>,
+    pub duration: Option<u64>,
+    pub transition: Option<TransitionKind>,
+    pub transition_duration: Option<u64>,
+    pub palette: Option<Palette>,
+    pub text_color: Option<TextColor>,
+    pub text_effect: Option<TextEffect>,
+    pub big_text: Option<bool>,
+}
+
+impl SceneDefaults {
+    /// Fills every field left unset here from `fallback`.
+    pub fn or(self, fallback: SceneDefaults) -> SceneDefaults {
+        SceneDefaults {
+            text: self.text.or(fallback.text),
+            duration: self.duration.or(fallback.duration),
+            transition: self.transition.or(fallback.transition),
+            transition_duration: self.transition_duration.or(fallback.transition_duration),
+            palette: self.palette.or(fallback.palette),
+            text_color: self.text_color.or(fallback.text_color),
+            text_effect: self.text_effect.or(fallback.text_effect),
+            big_text: self.big_text.or(fallback.big_text),
+        }
+    }
+}
+
+impl FromStr for Segment {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.trim().split(':');
+
+        let name = parts.next().unwrap_or_default();
+        let style = AnimationStyle::from_str(name, true)
+            .map_err(|_| format!("unknown style '{}' in segment '{}'", name, s))?;
+
+        let mut segment = Segment::new(style);
+
+        let mut rest = parts.peekable();
+        if let Some(field) = rest.peek() {
+            if !field.contains('=') {
+                let duration = field
+                    .parse()
+                    .map_err(|_| format!("invalid duration '{}' in segment '{}'", field, s))?;
+                segment.duration = Some(duration);
+                rest.next();
+            }
+        }
+
+        // Text may itself contain ':', so everything after `text=` belongs
+        // to it until the next known `key=` field.
+        let mut last_key: Option<&str> = None;
+        for field in rest {
+            match field.split_once('=') {
+                Some((key, value)) if is_option_key(key) => {
+                    segment.set_option(key, value)?;
+                    last_key = Some(key);
+                }
+                _ => match (last_key, field.split_once('=')) {
+                    (Some("text"), _) => segment.append_text(field),
+                    (_, Some((key, _))) => return Err(format!("unknown segment option '{}' in segment '{}'", key, s)),
+                    (_, None) => return Err(format!("expected KEY=VALUE, got '{}' in segment '{}'", field, s)),
+                },
+            }
+        }
+
+        Ok(segment)
+    }
+}
+
+fn is_option_key(key: &str) -> bool {
+    matches!(key, "text" | "transition" | "transition-duration")
+}
+
+impl Segment {
+    /// A segment that only names its style, leaving the rest to defaults.
+    pub fn new(style: AnimationStyle) -> Self {
+        Segment {
+            style,
+            duration: None,
+            text: None,
+            transition: None,
+            transition_duration: None,
+            palette: None,
+            text_color: None,
+            text_effect: None,
+            big_text: None,
+        }
+    }
+
+    fn set_option(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "text" => self.text = Some(value.to_string()),
+            "transition" => self.transition = Some(value.parse()?),
+            "transition-duration" => {
+                let duration = value
+                    .parse()
+                    .map_err(|_| format!("invalid transition-duration '{}'", value))?;
+                self.transition_duration = Some(duration);
+            }
+            _ => return Err(format!("unknown segment option '{}'", key)),
+        }
+        Ok(())
+    }
+
+    /// Resolves the final scene: `overrides` beat the segment's own fields,
+    /// which beat `defaults`, which beat the built-in defaults.
+    pub fn resolve(&self, overrides: &SceneDefaults, defaults: &SceneDefaults) -> Scene {
+        let text = overrides.text.as_ref().or(self.text.as_ref()).or(defaults.text.as_ref());
+        let duration = overrides.duration.or(self.duration).or(defaults.duration);
+        let transition = overrides.transition.or(self.transition).or(defaults.transition);
+        let transition_duration = overrides
+            .transition_duration
+            .or(self.transition_duration)
+            .or(defaults.transition_duration)
+            .unwrap_or(DEFAULT_TRANSITION_DURATION);
+        let palette = overrides.palette.as_ref().or(self.palette.as_ref()).or(defaults.palette.as_ref());
+
+        Scene {
+            style: self.style.clone(),
+            text: text.cloned().unwrap_or_default(),
+            duration: Duration::from_millis(duration.unwrap_or(DEFAULT_DURATION)),
+            transition: transition.map(|kind| Transition {
+                kind,
+                duration: Duration::from_millis(transition_duration),
+            }),
+            palette: palette.cloned(),
+            text_color: overrides.text_color.or(self.text_color).or(defaults.text_color),
+            text_effect: overrides.text_effect.or(self.text_effect).or(defaults.text_effect),
+            big_text: overrides.big_text.or(self.big_text).or(defaults.big_text),
+        }
+    }
+
+    fn append_text(&mut self, value: &str) {
+        let text = self.text.get_or_insert_with(String::new);
+        text.push(':');
+        text.push_str(value);
+    }
+}
+
+/// A parsed `--sequence` value: the segments in playback order.
+#[derive(Clone, Debug)]
+pub struct Sequence(pub Vec<Segment>);
+
+impl FromStr for Sequence {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Text may contain ',' too, so after a segment with text a new one
+        // only starts where the part after a ',' names a style.
+        let mut parts: Vec<String> = Vec::new();
+        for part in s.split(',') {
+            let name = part.trim().split(':').next().unwrap_or_default();
+            match parts.last_mut() {
+                Some(last) if last.contains(":text=") && AnimationStyle::from_str(name, true).is_err() => {
+                    last.push(',');
+                    last.push_str(part);
+                }
+                _ => parts.push(part.to_string()),
+            }
+        }
+        let segments = parts
+            .iter()
+            .filter(|part| !part.trim().is_empty())
+            .map(|part| Segment::from_str(part))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if segments.is_empty() {
+            return Err("sequence must contain at least one segment".to_string());
+        }
+        Ok(Sequence(segments))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segments(s: &str) -> Vec<Segment> {
+        s.parse::<Sequence>().unwrap().0
+    }
+
+    #[test]
+    fn duration_is_optional() {
+        let [segment] = &segments("waves")[..] else { panic!() };
+        assert_eq!(segment.style, AnimationStyle::Waves);
+        assert_eq!(segment.duration, None);
+
+        let [segment] = &segments("waves:text=Hi")[..] else { panic!() };
+        assert_eq!((segment.duration, segment.text.as_deref()), (None, Some("Hi")));
+    }
+
+    #[test]
+    fn text_may_contain_colons_and_commas() {
+        let parsed = segments("waves:2000:text=Hello, world: 10:30,explosion:1500:text=a,b");
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0].text.as_deref(), Some("Hello, world: 10:30"));
+        assert_eq!(parsed[0].duration, Some(2000));
+        assert_eq!(parsed[1].style, AnimationStyle::Explosion);
+        assert_eq!(parsed[1].text.as_deref(), Some("a,b"));
+    }
+
+    #[test]
+    fn a_comma_before_a_style_name_starts_a_segment() {
+        let parsed = segments("waves:text=Hi,rainbow,explosion:500");
+        let styles: Vec<_> = parsed.iter().map(|segment| segment.style.clone()).collect();
+        assert_eq!(styles, [AnimationStyle::Waves, AnimationStyle::Rainbow, AnimationStyle::Explosion]);
+        assert_eq!(parsed[0].text.as_deref(), Some("Hi"));
+        assert_eq!(parsed[2].duration, Some(500));
+    }
+
+    #[test]
+    fn text_ends_at_the_next_known_option() {
+        let [segment] = &segments("waves:text=a:b:transition=dissolve")[..] else { panic!() };
+        assert_eq!(segment.text.as_deref(), Some("a:b"));
+        assert_eq!(segment.transition, Some(TransitionKind::Dissolve));
+    }
+
+    #[test]
+    fn unknown_keys_and_styles_are_rejected() {
+        let err = "waves:color=red".parse::<Sequence>().unwrap_err();
+        assert!(err.contains("unknown segment option 'color'"), "{}", err);
+        let err = "waves:transition=dissolve:color=red".parse::<Sequence>().unwrap_err();
+        assert!(err.contains("unknown segment option 'color'"), "{}", err);
+        let err = "waves,bogus".parse::<Sequence>().unwrap_err();
+        assert!(err.contains("unknown style 'bogus'"), "{}", err);
+    }
+
+    #[test]
+    fn bad_durations_are_rejected() {
+        let err = "waves:2s".parse::<Sequence>().unwrap_err();
+        assert!(err.contains("invalid duration '2s'"), "{}", err);
+        let err = "waves:-1".parse::<Sequence>().unwrap_err();
+        assert!(err.contains("invalid duration '-1'"), "{}", err);
+        let err = "waves:transition-duration=soon".parse::<Sequence>().unwrap_err();
+        assert!(err.contains("invalid transition-duration 'soon'"), "{}", err);
+    }
+
+    #[test]
+    fn empty_sequences_are_rejected() {
+        assert!(", ,".parse::<Sequence>().is_err());
+    }
+}
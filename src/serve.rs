@@ -0,0 +1,166 @@
+use std::{
+    io::{self, BufWriter, Read, Write},
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+    sync::mpsc::{self, Sender},
+    thread,
+};
+
+use crate::control::Control;
+use crate::player::{play_to, PlayOptions, Scene};
+
+// Telnet protocol bytes (RFC 854) and the options we negotiate.
+const IAC: u8 = 255;
+const DONT: u8 = 254;
+const DO: u8 = 253;
+const WONT: u8 = 252;
+const WILL: u8 = 251;
+const SB: u8 = 250;
+const SE: u8 = 240;
+const IP: u8 = 244;
+const ECHO: u8 = 1;
+const SUPPRESS_GO_AHEAD: u8 = 3;
+const NAWS: u8 = 31;
+
+/// The size assumed until the client reports its own.
+const DEFAULT_SIZE: (u16, u16) = (80, 24);
+
+/// What a client's bytes amount to, once telnet commands are taken out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Event {
+    Resize(u16, u16),
+    Interrupt,
+    Byte(u8),
+}
+
+#[derive(Default)]
+enum State {
+    #[default]
+    Data,
+    Command,
+    /// Waiting for the option byte after WILL/WONT/DO/DONT.
+    Option,
+    Subnegotiation,
+    SubnegotiationCommand,
+}
+
+/// Splits a telnet byte stream into data and the window size reports
+/// (NAWS, RFC 1073) we asked for.
+#[derive(Default)]
+struct Telnet {
+    state: State,
+    subnegotiation: Vec<u8>,
+}
+
+impl Telnet {
+    fn feed(&mut self, byte: u8) -> Option<Event> {
+        match self.state {
+            State::Data if byte == IAC => self.state = State::Command,
+            State::Data => return Some(Event::Byte(byte)),
+            State::Command => {
+                self.state = State::Data;
+                match byte {
+                    // An escaped 255 is plain data.
+                    IAC => return Some(Event::Byte(IAC)),
+                    IP => return Some(Event::Interrupt),
+                    WILL | WONT | DO | DONT => self.state = State::Option,
+                    SB => {
+                        self.subnegotiation.clear();
+                        self.state = State::Subnegotiation;
+                    }
+                    _ => {}
+                }
+            }
+            State::Option => self.state = State::Data,
+            State::Subnegotiation if byte == IAC => self.state = State::SubnegotiationCommand,
+            State::Subnegotiation => self.subnegotiation.push(byte),
+            State::SubnegotiationCommand => match byte {
+                SE => {
+                    self.state = State::Data;
+                    if let [NAWS, w1, w0, h1, h0] = self.subnegotiation[..] {
+                        let (width, height) = (u16::from_be_bytes([w1, w0]), u16::from_be_bytes([h1, h0]));
+                        if width > 0 && height > 0 {
+                            return Some(Event::Resize(width, height));
+                        }
+                    }
+                }
+                _ => {
+                    self.subnegotiation.push(byte);
+                    self.state = State::Subnegotiation;
+                }
+            },
+        }
+        None
+    }
+}
+
+/// Accepts telnet clients on `addr` and plays `scenes` to each of them,
+/// every client with its own show at its own window size.
+pub fn serve(addr: impl ToSocketAddrs, scenes: Vec<Scene>, options: PlayOptions) -> io::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else { continue };
+        let (scenes, options) = (scenes.clone(), options.clone());
+        thread::spawn(move || session(stream, &scenes, &options));
+    }
+    Ok(())
+}
+
+fn session(stream: TcpStream, scenes: &[Scene], options: &PlayOptions) -> io::Result<()> {
+    stream.set_nodelay(true)?;
+    let mut out = BufWriter::new(stream.try_clone()?);
+    // Ask for the window size, and take over echoing so keys aren't shown
+    // and arrive one at a time.
+    out.write_all(&[IAC, DO, NAWS, IAC, WILL, ECHO, IAC, WILL, SUPPRESS_GO_AHEAD])?;
+    out.flush()?;
+
+    let (controls, receiver) = mpsc::channel();
+    thread::spawn(move || read_client(stream, controls));
+    play_to(&mut out, DEFAULT_SIZE, scenes, options, &receiver)
+}
+
+/// Passes size reports on to the player, and stops it on Ctrl-C, `q` or
+/// when the client goes away.
+fn read_client(mut stream: TcpStream, controls: Sender<Control>) {
+    let mut telnet = Telnet::default();
+    let mut bytes = [0; 256];
+    while let Ok(n @ 1..) = stream.read(&mut bytes) {
+        for &byte in &bytes[..n] {
+            let control = match telnet.feed(byte) {
+                Some(Event::Resize(width, height)) => Control::Resize(width, height),
+                Some(Event::Interrupt) | Some(Event::Byte(3 | 4 | b'q')) => Control::Cancel,
+                _ => continue,
+            };
+            let cancel = control == Control::Cancel;
+            if controls.send(control).is_err() || cancel {
+                return;
+            }
+        }
+    }
+    let _ = controls.send(Control::Cancel);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feed(bytes: &[u8]) -> Vec<Event> {
+        let mut telnet = Telnet::default();
+        bytes.iter().filter_map(|&byte| telnet.feed(byte)).collect()
+    }
+
+    #[test]
+    fn naws_with_an_escaped_255_reports_the_size() {
+        let events = feed(&[IAC, SB, NAWS, 0, IAC, IAC, 0, 24, IAC, SE, b'q']);
+        assert_eq!(events, [Event::Resize(255, 24), Event::Byte(b'q')]);
+    }
+
+    #[test]
+    fn iac_iac_is_a_data_byte() {
+        assert_eq!(feed(&[b'a', IAC, IAC, b'b']), [Event::Byte(b'a'), Event::Byte(IAC), Event::Byte(b'b')]);
+    }
+
+    #[test]
+    fn option_negotiation_and_interrupt() {
+        assert_eq!(feed(&[IAC, WILL, NAWS, IAC, DO, ECHO, IAC, IP]), [Event::Interrupt]);
+    }
+}
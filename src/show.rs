@@ -0,0 +1,379 @@
+use std::{fmt, fs, path::Path};
+
+use serde::de::{DeserializeSeed, IgnoredAny, IntoDeserializer, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+
+use crate::config::Settings;
+use crate::loader::{self, LoadError};
+use crate::player::Scene;
+use crate::sequence::{SceneDefaults, Segment};
+
+/// A whole show described in a TOML or JSON file, played with `reveal play`.
+/// Its top level takes the same keys as a preset; flags given on the command
+/// line win. Scenes can set their own text, colors and effects.
+///
+/// ```toml
+/// loops = 2
+/// palette = "sunset"
+/// text-effect = "outline"
+///
+/// [defaults]
+/// transition = "crossfade"
+///
+/// [[scene]]
+/// style = "waves"
+/// text = "Loading"
+/// duration = 2000
+/// palette = "ocean"
+/// ```
+#[derive(Debug)]
+pub struct Show {
+    /// Everything set at the top of the file. Its text, duration and
+    /// transition apply to scenes that set none.
+    pub settings: Settings,
+    pub defaults: SceneDefaults,
+    pub scenes: Vec<Segment>,
+}
+
+/// The keys of a show that aren't [`Settings`].
+#[derive(Deserialize)]
+struct Scenes {
+    #[serde(default)]
+    defaults: SceneDefaults,
+    #[serde(alias = "scenes")]
+    scene: Vec<Segment>,
+}
+
+/// Every top-level key [`Scenes`] reads.
+const SCENE_KEYS: [&str; 3] = ["defaults", "scene", "scenes"];
+
+impl Show {
+    /// Reads a show, picking JSON for `.json` files and TOML otherwise.
+    pub fn load(path: &Path) -> Result<Show, LoadError> {
+        let source = fs::read_to_string(path).map_err(LoadError::Io)?;
+        let mut show = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => Show::from_json(&source)?,
+            _ => Show::from_toml(&source)?,
+        };
+        show.settings.resolve(path.parent().unwrap_or(Path::new("")), "")?;
+        Ok(show)
+    }
+
+    pub fn from_toml(source: &str) -> Result<Show, LoadError> {
+        let ShowSettings(settings) = loader::from_toml(source)?;
+        let scenes: Scenes = loader::from_toml(source).map_err(scene_path)?;
+        let show = Show { settings, defaults: scenes.defaults, scenes: scenes.scene };
+        show.validate(|i| toml_scene_line(source, i))?;
+        Ok(show)
+    }
+
+    pub fn from_json(source: &str) -> Result<Show, LoadError> {
+        let ShowSettings(settings) = loader::from_json(source)?;
+        let scenes: Scenes = loader::from_json(source).map_err(scene_path)?;
+        let show = Show { settings, defaults: scenes.defaults, scenes: scenes.scene };
+        show.validate(|i| json_scene_line(source, i))?;
+        Ok(show)
+    }
+
+    /// Checks what the types can't; `line` finds where scene `i` starts.
+    fn validate(&self, line: impl Fn(usize) -> Option<usize>) -> Result<(), LoadError> {
+        if self.scenes.is_empty() {
+            return Err(LoadError::Invalid {
+                field: "scene".to_string(),
+                message: "a show needs at least one scene".to_string(),
+            });
+        }
+        if self.settings.style.is_some() {
+            return Err(LoadError::Invalid {
+                field: "style".to_string(),
+                message: "set the style on each scene instead".to_string(),
+            });
+        }
+        self.settings.clone().resolve(Path::new(""), "")?;
+
+        // An infinite scene never hands over, so anything after it is dead.
+        let last = self.scenes.len() - 1;
+        let duration = self.defaults.duration.or(self.settings.duration);
+        for (i, scene) in self.scenes.iter().enumerate() {
+            if i != last && scene.duration.or(duration) == Some(0) {
+                let at = line(i).map(|line| format!(" (scene at line {})", line)).unwrap_or_default();
+                return Err(LoadError::Invalid {
+                    field: format!("scene[{}].duration", i),
+                    message: format!("0 (infinite) is only allowed on the last scene{}", at),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Resolves every scene, with `overrides` (usually command-line flags)
+    /// taking precedence over anything in the file and `fallback` (presets,
+    /// the config file) filling whatever the file leaves unset.
+    pub fn scenes(&self, overrides: &SceneDefaults, fallback: &SceneDefaults) -> Vec<Scene> {
+        let defaults = self
+            .defaults
+            .clone()
+            .or(self.settings.scene_defaults())
+            .or(fallback.clone());
+        self.scenes
+            .iter()
+            .map(|segment| segment.resolve(overrides, &defaults))
+            .collect()
+    }
+}
+
+/// Spells scene paths `scene[N]` whichever key the file uses, as
+/// [`Show::validate`] does.
+fn scene_path(err: LoadError) -> LoadError {
+    match err {
+        LoadError::Parse { field, message } => LoadError::Parse {
+            field: match field.strip_prefix("scenes") {
+                Some(rest) => format!("scene{}", rest),
+                None => field,
+            },
+            message,
+        },
+        err => err,
+    }
+}
+
+/// The [`Settings`] at the top of a show: every key but [`SCENE_KEYS`].
+struct ShowSettings(Settings);
+
+impl<'de> Deserialize<'de> for ShowSettings {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Settings::deserialize(SkipScenes(deserializer)).map(ShowSettings)
+    }
+}
+
+/// Wraps a deserializer, its visitor and then its map to pass over
+/// [`SCENE_KEYS`], so [`Settings`] still rejects any other unknown key.
+struct SkipScenes<T>(T);
+
+impl<'de, D: Deserializer<'de>> Deserializer<'de> for SkipScenes<D> {
+    type Error = D::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, D::Error> {
+        self.0.deserialize_any(SkipScenes(visitor))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, D::Error> {
+        self.0.deserialize_struct(name, fields, SkipScenes(visitor))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map enum identifier ignored_any
+    }
+}
+
+impl<'de, V: Visitor<'de>> Visitor<'de> for SkipScenes<V> {
+    type Value = V::Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.expecting(f)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<V::Value, A::Error> {
+        self.0.visit_map(SkipScenes(map))
+    }
+}
+
+impl<'de, A: MapAccess<'de>> MapAccess<'de> for SkipScenes<A> {
+    type Error = A::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, A::Error> {
+        while let Some(key) = self.0.next_key::<String>()? {
+            if !SCENE_KEYS.contains(&key.as_str()) {
+                return seed.deserialize(key.into_deserializer()).map(Some);
+            }
+            self.0.next_value::<IgnoredAny>()?;
+        }
+        Ok(None)
+    }
+
+    fn next_value_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<T::Value, A::Error> {
+        self.0.next_value_seed(seed)
+    }
+}
+
+/// The 1-based line of the `i`th `[[scene]]` table.
+fn toml_scene_line(source: &str, i: usize) -> Option<usize> {
+    source
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| matches!(line.trim(), "[[scene]]" | "[[scenes]]"))
+        .nth(i)
+        .map(|(n, _)| n + 1)
+}
+
+/// The 1-based line of the `i`th object in the top-level `"scenes"` array.
+fn json_scene_line(source: &str, i: usize) -> Option<usize> {
+    let (mut depth, mut line, mut in_string, mut escaped) = (0, 1, false, false);
+    let (mut key, mut last_key) = (String::new(), String::new());
+    let mut scenes_depth = None;
+    let mut found = 0;
+    for c in source.chars() {
+        if c == '\n' {
+            line += 1;
+        }
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => {
+                    in_string = false;
+                    last_key = std::mem::take(&mut key);
+                }
+                _ => key.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '[' if depth == 1 && scenes_depth.is_none() && matches!(last_key.as_str(), "scenes" | "scene") => {
+                depth += 1;
+                scenes_depth = Some(depth);
+            }
+            '{' if Some(depth) == scenes_depth => {
+                if found == i {
+                    return Some(line);
+                }
+                found += 1;
+                depth += 1;
+            }
+            '{' | '[' => depth += 1,
+            '}' | ']' => {
+                if Some(depth) == scenes_depth {
+                    return None;
+                }
+                depth -= 1;
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::legibility::TextEffect;
+    use crate::motion::ReducedMotion;
+    use crate::palette::Palette;
+
+    fn invalid_field(result: Result<Show, LoadError>) -> String {
+        match result {
+            Err(LoadError::Invalid { field, .. }) | Err(LoadError::Parse { field, .. }) => field,
+            other => panic!("expected an error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn the_top_level_takes_any_setting() {
+        let show = Show::from_toml(
+            "loops = 2\nseed = 7\nstats = true\nreduced-motion = \"static\"\nmax-bandwidth = \"20k\"\n\
+             blocks = [\"v1:valign=bottom\"]\n\n[[scene]]\nstyle = \"waves\"\n",
+        )
+        .unwrap();
+        assert_eq!(show.settings.loops, Some(2));
+        assert_eq!(show.settings.seed, Some(7));
+        assert_eq!(show.settings.stats, Some(true));
+        assert_eq!(show.settings.reduced_motion, Some(ReducedMotion::Static));
+        assert_eq!(show.settings.max_bandwidth, Some(20_000));
+        assert_eq!(show.settings.blocks.map(|blocks| blocks.len()), Some(1));
+    }
+
+    #[test]
+    fn scenes_override_the_show_and_flags_override_scenes() {
+        let show = Show::from_toml(
+            "text = \"Shared\"\ntext-effect = \"shadow\"\n\n\
+             [[scene]]\nstyle = \"waves\"\npalette = \"ocean\"\ntext-effect = \"outline\"\nbig-text = true\n\n\
+             [[scene]]\nstyle = \"rainbow\"\nduration = 100\n",
+        )
+        .unwrap();
+        let scenes = show.scenes(&SceneDefaults::default(), &SceneDefaults::default());
+        assert_eq!(scenes[0].palette, "ocean".parse().ok());
+        assert_eq!(scenes[0].text_effect, Some(TextEffect::Outline));
+        assert_eq!(scenes[0].big_text, Some(true));
+        assert_eq!(scenes[1].palette, None);
+        assert_eq!(scenes[1].text_effect, Some(TextEffect::Shadow));
+        assert_eq!(scenes[1].text, "Shared");
+        assert_eq!(scenes[1].duration, Duration::from_millis(100));
+
+        let neon: Palette = "neon".parse().unwrap();
+        let flags = SceneDefaults { palette: Some(neon.clone()), ..SceneDefaults::default() };
+        let scenes = show.scenes(&flags, &SceneDefaults::default());
+        assert!(scenes.iter().all(|scene| scene.palette.as_ref() == Some(&neon)));
+    }
+
+    #[test]
+    fn unknown_top_level_keys_are_rejected() {
+        assert!(Show::from_toml("bogus = 1\n\n[[scene]]\nstyle = \"waves\"\n").is_err());
+        assert!(Show::from_json(r#"{"bogus": 1, "scenes": [{"style": "waves"}]}"#).is_err());
+    }
+
+    #[test]
+    fn a_show_needs_a_scene_with_its_own_style() {
+        assert_eq!(invalid_field(Show::from_json(r#"{"scenes": []}"#)), "scene");
+        assert_eq!(invalid_field(Show::from_toml("style = \"waves\"\n\n[[scene]]\nstyle = \"waves\"\n")), "style");
+        assert_eq!(invalid_field(Show::from_toml("fps = 1000\n\n[[scene]]\nstyle = \"waves\"\n")), "fps");
+    }
+
+    #[test]
+    fn only_the_last_scene_may_be_infinite() {
+        let source = "[defaults]\nduration = 0\n\n[[scene]]\nstyle = \"waves\"\n\n[[scene]]\nstyle = \"rainbow\"\n";
+        match Show::from_toml(source) {
+            Err(LoadError::Invalid { field, message }) => {
+                assert_eq!(field, "scene[0].duration");
+                assert!(message.ends_with("(scene at line 4)"), "{}", message);
+            }
+            other => panic!("expected an error, got {:?}", other),
+        }
+        assert!(Show::from_toml("[[scene]]\nstyle = \"waves\"\nduration = 0\n").is_ok());
+    }
+
+    #[test]
+    fn scene_paths_are_spelled_the_same_for_either_key() {
+        let toml = invalid_field(Show::from_toml("[[scenes]]\nstyle = \"nope\"\n"));
+        let json = invalid_field(Show::from_json(r#"{"scenes": [{"style": "waves"}, {"style": "nope"}]}"#));
+        let infinite = invalid_field(Show::from_json(r#"{"scene": [{"style": "waves", "duration": 0}, {"style": "waves"}]}"#));
+        assert_eq!(toml, "scene[0].style");
+        assert_eq!(json, "scene[1].style");
+        assert_eq!(infinite, "scene[0].duration");
+    }
+
+    #[test]
+    fn toml_scene_lines_count_either_spelling() {
+        let source = "loops = 2\n\n[[scene]]\nstyle = \"waves\"\n\n  [[scenes]]\nstyle = \"rainbow\"\n";
+        assert_eq!(toml_scene_line(source, 0), Some(3));
+        assert_eq!(toml_scene_line(source, 1), Some(6));
+        assert_eq!(toml_scene_line(source, 2), None);
+    }
+
+    #[test]
+    fn json_scene_lines_skip_nested_values_and_strings() {
+        let source = r##"{
+  "palette": ["#000000", "#ffffff"],
+  "defaults": {"text": "[{ not a scene"},
+  "scenes": [
+    {"style": "waves", "text": "a \"}\" brace"},
+    {
+      "style": "rainbow"
+    }
+  ],
+  "title": {"ignored": [{}]}
+}"##;
+        assert_eq!(json_scene_line(source, 0), Some(5));
+        assert_eq!(json_scene_line(source, 1), Some(6));
+        assert_eq!(json_scene_line(source, 2), None);
+    }
+}
@@ -0,0 +1,119 @@
+use std::{
+    io::{self, Write},
+    time::{Duration, Instant},
+};
+
+use termion::color;
+
+use crate::buffer::Buffer;
+use crate::terminal::Rect;
+
+const OVERLAY_COLOR: color::Rgb = color::Rgb(255, 255, 0);
+
+/// Counts the bytes written through it, to measure what each frame costs
+/// on the wire.
+pub struct CountingWriter<'a, W: Write> {
+    inner: &'a mut W,
+    pub written: u64,
+}
+
+impl<'a, W: Write> CountingWriter<'a, W> {
+    pub fn new(inner: &'a mut W) -> Self {
+        Self { inner, written: 0 }
+    }
+}
+
+impl<W: Write> Write for CountingWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// What one frame cost.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Frame {
+    /// Time spent drawing the frame and writing it out.
+    pub render_time: Duration,
+    pub bytes: u64,
+    pub cells_changed: usize,
+}
+
+/// Per-frame costs of a run, for `--stats`.
+pub struct Stats {
+    /// The seed the run used, to reproduce it with `--seed`.
+    seed: u64,
+    frames: Vec<Frame>,
+    last_shown: Option<Instant>,
+    /// Smoothed time between frames reaching the screen.
+    interval: Option<f32>,
+}
+
+impl Stats {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            frames: Vec::new(),
+            last_shown: None,
+            interval: None,
+        }
+    }
+
+    pub fn record(&mut self, frame: Frame) {
+        let now = Instant::now();
+        if let Some(last) = self.last_shown {
+            let interval = (now - last).as_secs_f32();
+            self.interval = Some(self.interval.map_or(interval, |smoothed| smoothed * 0.9 + interval * 0.1));
+        }
+        self.last_shown = Some(now);
+        self.frames.push(frame);
+    }
+
+    /// Writes the latest figures over the top-left corner of `region`.
+    pub fn draw_overlay(&self, buffer: &mut Buffer, region: Rect) {
+        let Some(frame) = self.frames.last() else { return };
+        let fps = self.interval.filter(|interval| *interval > 0.0).map_or(0.0, |interval| 1.0 / interval);
+        let line = format!(
+            " {:.1} fps  {:.1} ms  {} B  {} cells ",
+            fps,
+            frame.render_time.as_secs_f64() * 1000.0,
+            frame.bytes,
+            frame.cells_changed
+        );
+        let line: String = line.chars().take(region.width as usize).collect();
+        if region.height > 0 {
+            buffer.put_str(region.x, region.y, &line, OVERLAY_COLOR);
+        }
+    }
+
+    /// Writes a summary of the run: frame time mean, 95th percentile and
+    /// maximum, bytes written and the seed. Lines end in `\r\n` as the terminal may
+    /// still be in raw mode.
+    pub fn report<W: Write>(&self, out: &mut W, dropped: Option<u64>) -> io::Result<()> {
+        if self.frames.is_empty() {
+            return write!(out, "stats: no frames rendered (seed {})\r\n", self.seed);
+        }
+        let mut times: Vec<Duration> = self.frames.iter().map(|frame| frame.render_time).collect();
+        times.sort();
+        let count = times.len();
+        let mean = times.iter().sum::<Duration>() / count as u32;
+        let p95 = times[(count * 95).div_ceil(100).saturating_sub(1)];
+        let max = times[count - 1];
+        let bytes: u64 = self.frames.iter().map(|frame| frame.bytes).sum();
+        let ms = |time: Duration| time.as_secs_f64() * 1000.0;
+
+        write!(out, "stats: {} frames\r\n", count)?;
+        write!(out, "  frame time: mean {:.2} ms, p95 {:.2} ms, max {:.2} ms\r\n", ms(mean), ms(p95), ms(max))?;
+        write!(out, "  bytes written: {} total, {} per frame\r\n", bytes, bytes / count as u64)?;
+        if let Some(dropped) = dropped {
+            write!(out, "  frames dropped: {}\r\n", dropped)?;
+        }
+        write!(out, "  seed: {}\r\n", self.seed)?;
+        out.flush()
+    }
+}
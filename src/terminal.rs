@@ -1,27 +1,154 @@
-use termion::terminal_size;
+use std::str::FromStr;
+
+use serde::Deserialize;
 use termion::color;
 
-pub struct Terminal {
+/// A rectangular region of the screen, in 0-based cells. In a file it is
+/// written `[x, y, width, height]`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(from = "[u16; 4]")]
+pub struct Rect {
+    pub x: u16,
+    pub y: u16,
     pub width: u16,
     pub height: u16,
 }
 
-impl Terminal {
-    pub fn new() -> Self {
-        let (w, h) = terminal_size().unwrap();
-        Self {
-            width: w,
-            height: h,
-        }
+impl Rect {
+    pub fn new(x: u16, y: u16, width: u16, height: u16) -> Self {
+        Self { x, y, width, height }
     }
 
     pub fn center_pos(&self) -> (u16, u16) {
-        (self.width / 2, self.height / 2)
+        (self.x + self.width / 2, self.y + self.height / 2)
+    }
+
+    pub fn right(&self) -> u16 {
+        self.x.saturating_add(self.width)
+    }
+
+    pub fn bottom(&self) -> u16 {
+        self.y.saturating_add(self.height)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    pub fn contains(&self, x: u16, y: u16) -> bool {
+        x >= self.x && x < self.right() && y >= self.y && y < self.bottom()
+    }
+
+    /// Shrinks the region by `margin` cells on every side.
+    pub fn inset(&self, margin: u16) -> Rect {
+        Rect::new(
+            self.x.saturating_add(margin),
+            self.y.saturating_add(margin),
+            self.width.saturating_sub(margin.saturating_mul(2)),
+            self.height.saturating_sub(margin.saturating_mul(2)),
+        )
+    }
+
+    /// The overlap of both regions, empty if they don't touch.
+    pub fn intersect(&self, other: &Rect) -> Rect {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let right = self.right().min(other.right());
+        let bottom = self.bottom().min(other.bottom());
+        Rect::new(x, y, right.saturating_sub(x), bottom.saturating_sub(y))
+    }
+
+    /// Splits into `n` side-by-side columns, giving leftover cells to the last one.
+    pub fn split_columns(&self, n: u16) -> Vec<Rect> {
+        let n = n.max(1);
+        let width = self.width / n;
+        (0..n)
+            .map(|i| {
+                let x = self.x + i * width;
+                let w = if i == n - 1 { self.right() - x } else { width };
+                Rect::new(x, self.y, w, self.height)
+            })
+            .collect()
+    }
+
+    /// Splits into `n` stacked rows, giving leftover cells to the last one.
+    pub fn split_rows(&self, n: u16) -> Vec<Rect> {
+        let n = n.max(1);
+        let height = self.height / n;
+        (0..n)
+            .map(|i| {
+                let y = self.y + i * height;
+                let h = if i == n - 1 { self.bottom() - y } else { height };
+                Rect::new(self.x, y, self.width, h)
+            })
+            .collect()
     }
 }
 
-#[derive(Clone)]
+/// `[x, y, width, height]`, as written in a file.
+impl From<[u16; 4]> for Rect {
+    fn from([x, y, width, height]: [u16; 4]) -> Self {
+        Rect::new(x, y, width, height)
+    }
+}
+
+/// Parses `X,Y,WIDTH,HEIGHT`, as taken by `--viewport`.
+impl FromStr for Rect {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts = s
+            .split(',')
+            .map(|part| part.trim().parse::<u16>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| format!("invalid region '{}', expected X,Y,WIDTH,HEIGHT", s))?;
+        match parts[..] {
+            [x, y, width, height] => Ok(Rect::new(x, y, width, height)),
+            _ => Err(format!("invalid region '{}', expected X,Y,WIDTH,HEIGHT", s)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Cell {
     pub char: char,
     pub color: color::Rgb,
-}
\ No newline at end of file
+    /// Background color; `None` leaves the terminal's own.
+    pub background: Option<color::Rgb>,
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Self {
+            char: ' ',
+            color: color::Rgb(0, 0, 0),
+            background: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn columns_share_the_width_and_the_last_takes_the_rest() {
+        let columns = Rect::new(2, 1, 10, 4).split_columns(3);
+        assert_eq!(columns, [Rect::new(2, 1, 3, 4), Rect::new(5, 1, 3, 4), Rect::new(8, 1, 4, 4)]);
+        assert_eq!(Rect::new(0, 0, 5, 5).split_columns(0), [Rect::new(0, 0, 5, 5)]);
+    }
+
+    #[test]
+    fn rows_share_the_height_and_the_last_takes_the_rest() {
+        let rows = Rect::new(1, 2, 6, 7).split_rows(2);
+        assert_eq!(rows, [Rect::new(1, 2, 6, 3), Rect::new(1, 5, 6, 4)]);
+    }
+
+    #[test]
+    fn contains_includes_the_top_left_and_excludes_the_far_edges() {
+        let rect = Rect::new(2, 3, 4, 5);
+        assert!(rect.contains(2, 3) && rect.contains(5, 7));
+        assert!(!rect.contains(6, 3) && !rect.contains(2, 8) && !rect.contains(1, 3));
+        assert!(!Rect::new(0, 0, 0, 3).contains(0, 0));
+    }
+}
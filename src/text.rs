@@ -0,0 +1,63 @@
+use std::{
+    fs,
+    io::{self, BufRead, Read},
+    path::{Path, PathBuf},
+    sync::mpsc::Sender,
+    thread,
+    time::Duration,
+};
+
+use crate::control::Control;
+
+/// Collapses multi-line input (e.g. `fortune` output) onto a single line.
+pub fn normalize(raw: &str) -> String {
+    raw.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Reads all of stdin, for `--text -`.
+pub fn read_stdin() -> io::Result<String> {
+    let mut raw = String::new();
+    io::stdin().read_to_string(&mut raw)?;
+    Ok(normalize(&raw))
+}
+
+pub fn read_file(path: &Path) -> io::Result<String> {
+    Ok(normalize(&fs::read_to_string(path)?))
+}
+
+/// Reads the first line of stdin now and sends every later line to the player.
+pub fn watch_stdin(controls: Sender<Control>) -> io::Result<String> {
+    let mut lines = io::stdin().lock().lines();
+    let first = lines.next().transpose()?.unwrap_or_default();
+    drop(lines);
+
+    thread::spawn(move || {
+        for line in io::stdin().lock().lines() {
+            let Ok(line) = line else { break };
+            if controls.send(Control::SetText(normalize(&line))).is_err() {
+                break;
+            }
+        }
+    });
+    Ok(normalize(&first))
+}
+
+/// Reads `path` now and polls it for changes, sending new contents to the player.
+pub fn watch_file(path: PathBuf, controls: Sender<Control>) -> io::Result<String> {
+    let initial = read_file(&path)?;
+
+    let mut last = initial.clone();
+    thread::spawn(move || loop {
+        thread::sleep(Duration::from_millis(250));
+        // The file may briefly vanish while an editor replaces it.
+        if let Ok(text) = read_file(&path) {
+            if text != last {
+                if controls.send(Control::SetText(text.clone())).is_err() {
+                    break;
+                }
+                last = text;
+            }
+        }
+    });
+    Ok(initial)
+}
@@ -0,0 +1,287 @@
+use std::{fs, path::Path};
+
+use serde_json::Value;
+use termion::color::Rgb;
+
+use crate::color::{parse_hex, shade};
+use crate::loader::LoadError;
+use crate::palette::Palette;
+
+/// Token names each role is taken from, most specific first. A token matches
+/// when its name is one of these or ends in `-` and one of these, so
+/// `color.brand.text` counts as `text`. Of several matches the closest wins:
+/// `color.text` over `button.text`, and the first in the file on a tie.
+const TEXT: [&str; 3] = ["text-color", "text", "foreground"];
+const TEXT_BACKGROUND: [&str; 2] = ["text-background", "text-bg"];
+const ACCENT: [&str; 2] = ["accent", "highlight"];
+const BRAND: [&str; 4] = ["primary", "secondary", "tertiary", "brand"];
+
+/// Name parts that only group tokens, so `color.text` is as close to `text`
+/// as `text` itself.
+const GROUPS: [&str; 3] = ["color", "colors", "theme"];
+
+/// How deep `var(--a)` / `{a}` references are followed.
+const MAX_REFERENCE_DEPTH: usize = 8;
+
+/// Brand colors mapped onto the roles reveal draws with. Roles left unset
+/// keep each style's own look.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Theme {
+    /// Colors every style draws with; an explicit palette wins over it.
+    pub palette: Option<Palette>,
+    pub text: Option<Rgb>,
+    /// Drawn behind the text, padded by a space on either side.
+    pub text_background: Option<Rgb>,
+    /// Progress bars, borders and big text.
+    pub accent: Option<Rgb>,
+}
+
+impl Theme {
+    /// Reads a design-token JSON file, or CSS custom properties from a
+    /// `.css` file.
+    ///
+    /// JSON tokens may be plain strings or objects with a `$value` (or
+    /// `value`), nested to any depth: `{"color": {"text": {"$value": "#fff"}}}`
+    /// is the token `color-text`. Palettes come from tokens named `palette`
+    /// (or an array of that name) in the order the file lists them, else
+    /// from the `primary`, `secondary`, `tertiary` and `brand` colors.
+    pub fn load(path: &Path) -> Result<Theme, LoadError> {
+        let source = fs::read_to_string(path).map_err(LoadError::Io)?;
+        let tokens = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("css") => css_tokens(&source),
+            _ => json_tokens(&source)?,
+        };
+        let theme = Theme::from_tokens(&tokens);
+        if theme == Theme::default() {
+            return Err(LoadError::Invalid {
+                field: "theme".to_string(),
+                message: "no text, background, accent, palette or brand colors found".to_string(),
+            });
+        }
+        Ok(theme)
+    }
+
+    /// Maps `(name, value)` tokens onto the roles. Names are lowercase and
+    /// dash-separated; values that aren't colors are skipped.
+    pub fn from_tokens(tokens: &[(String, String)]) -> Theme {
+        let colors: Vec<(&str, Rgb)> = tokens
+            .iter()
+            .filter_map(|(name, _)| Some((name.as_str(), parse_color(&resolve(tokens, name)?)?)))
+            .collect();
+        let role = |names: &[&str]| {
+            names.iter().find_map(|role| {
+                colors
+                    .iter()
+                    .filter(|(name, _)| is_named(name, role))
+                    .min_by_key(|(name, _)| distance(name))
+                    .map(|(_, color)| *color)
+            })
+        };
+
+        let mut stops: Vec<Rgb> = colors
+            .iter()
+            .filter(|(name, _)| name.split('-').any(|part| part == "palette"))
+            .map(|(_, color)| *color)
+            .collect();
+        if stops.is_empty() {
+            stops = BRAND.iter().filter_map(|name| role(&[name])).collect();
+        }
+        let palette = match stops[..] {
+            [] => None,
+            // One brand color still makes a gradient, from dark to full.
+            [only] => Some(Palette::Gradient(vec![shade(only, 0.2), only])),
+            _ => Some(Palette::Gradient(stops)),
+        };
+
+        Theme {
+            palette,
+            text: role(&TEXT),
+            text_background: role(&TEXT_BACKGROUND),
+            accent: role(&ACCENT),
+        }
+    }
+}
+
+fn is_named(name: &str, role: &str) -> bool {
+    name == role || name.strip_suffix(role).is_some_and(|rest| rest.ends_with('-'))
+}
+
+/// How many parts of `name` say more than which role it is.
+fn distance(name: &str) -> usize {
+    name.split('-').filter(|part| !GROUPS.contains(part)).count()
+}
+
+/// Token names as `a-b-c`, whatever separators the file used.
+fn normalize(name: &str) -> String {
+    name.trim()
+        .trim_start_matches("--")
+        .to_lowercase()
+        .replace(['.', '_', ' ', '/'], "-")
+}
+
+/// `--name: value;` declarations, wherever they are in the file.
+fn css_tokens(source: &str) -> Vec<(String, String)> {
+    let mut text = String::new();
+    let mut rest = source;
+    while let Some(start) = rest.find("/*") {
+        text.push_str(&rest[..start]);
+        rest = rest[start..].find("*/").map_or("", |end| &rest[start + end + 2..]);
+    }
+    text.push_str(rest);
+
+    text.split([';', '{', '}'])
+        .filter_map(|declaration| {
+            let (name, value) = declaration.trim().split_once(':')?;
+            name.trim().starts_with("--").then(|| (normalize(name), value.trim().to_string()))
+        })
+        .collect()
+}
+
+fn json_tokens(source: &str) -> Result<Vec<(String, String)>, LoadError> {
+    let value: Value = serde_json::from_str(source).map_err(|err| LoadError::Parse {
+        field: ".".to_string(),
+        message: err.to_string(),
+    })?;
+    let mut tokens = Vec::new();
+    flatten(&value, String::new(), &mut tokens);
+    Ok(tokens)
+}
+
+fn flatten(value: &Value, name: String, tokens: &mut Vec<(String, String)>) {
+    let join = |part: &str| normalize(&if name.is_empty() { part.to_string() } else { format!("{}-{}", name, part) });
+    match value {
+        Value::String(value) => tokens.push((name, value.clone())),
+        Value::Object(map) => {
+            if let Some(value) = map.get("$value").or_else(|| map.get("value")) {
+                return flatten(value, name, tokens);
+            }
+            for (key, value) in map {
+                if !key.starts_with('$') {
+                    flatten(value, join(key), tokens);
+                }
+            }
+        }
+        Value::Array(values) => {
+            for (i, value) in values.iter().enumerate() {
+                flatten(value, join(&i.to_string()), tokens);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The value of token `name`, following `var(--other)` and `{other}`
+/// references.
+fn resolve(tokens: &[(String, String)], name: &str) -> Option<String> {
+    let mut value = tokens.iter().find(|(token, _)| token == name)?.1.clone();
+    for _ in 0..MAX_REFERENCE_DEPTH {
+        let trimmed = value.trim();
+        let reference = if let Some(inner) = trimmed.strip_prefix("var(").and_then(|rest| rest.strip_suffix(')')) {
+            // `var(--a, fallback)` uses the fallback when `--a` is missing.
+            let (target, fallback) = inner.split_once(',').map_or((inner, None), |(a, b)| (a, Some(b.trim())));
+            match tokens.iter().find(|(token, _)| *token == normalize(target)) {
+                Some((_, value)) => value.clone(),
+                None => fallback?.to_string(),
+            }
+        } else if let Some(inner) = trimmed.strip_prefix('{').and_then(|rest| rest.strip_suffix('}')) {
+            tokens.iter().find(|(token, _)| *token == normalize(inner))?.1.clone()
+        } else {
+            return Some(value);
+        };
+        value = reference;
+    }
+    None
+}
+
+/// `#rrggbb`, `#rgb` or `rgb(r, g, b)` (alpha is ignored).
+fn parse_color(value: &str) -> Option<Rgb> {
+    let value = value.trim();
+    if value.starts_with('#') {
+        return parse_hex(value).ok();
+    }
+    let inner = value
+        .strip_prefix("rgba(")
+        .or_else(|| value.strip_prefix("rgb("))?
+        .strip_suffix(')')?;
+    let channels: Vec<u8> = inner
+        .split([',', ' ', '/'])
+        .filter(|part| !part.is_empty())
+        .take(3)
+        .map(|part| part.trim().parse::<f32>().ok().map(|c| c.clamp(0.0, 255.0).round() as u8))
+        .collect::<Option<_>>()?;
+    match channels[..] {
+        [r, g, b] => Some(Rgb(r, g, b)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JSON: &str = r##"{
+        "color": {
+            "palette": {"low": {"$value": "#000000"}, "mid": {"$value": "#888888"}, "high": {"$value": "#ffffff"}},
+            "background": {"$value": "#101010"},
+            "text": {"$value": "{color.brand.light}"},
+            "brand": {"light": {"$value": "#fafafa"}, "$description": "ignored"}
+        },
+        "button": {"text": {"$value": "#ff0000"}, "accent": {"$value": "rgb(0, 128, 255)"}}
+    }"##;
+
+    const CSS: &str = "
+        /* --text-color: #000000; */
+        :root {
+            --brand-primary: #aa0044;
+            --brand-secondary: #fc0;
+            --text-color: var(--missing, #ffffff);
+            --text-bg: rgba(10, 20, 30, 0.5);
+            --accent: var(--brand-secondary);
+            --spacing: 4px;
+        }
+    ";
+
+    #[test]
+    fn json_tokens_map_onto_roles() {
+        let theme = Theme::from_tokens(&json_tokens(JSON).unwrap());
+        assert_eq!(
+            theme.palette,
+            Some(Palette::Gradient(vec![Rgb(0, 0, 0), Rgb(136, 136, 136), Rgb(255, 255, 255)]))
+        );
+        // `color.text` is closer than `button.text`, and follows its alias.
+        assert_eq!(theme.text, Some(Rgb(250, 250, 250)));
+        // The page background is not the text's.
+        assert_eq!(theme.text_background, None);
+        assert_eq!(theme.accent, Some(Rgb(0, 128, 255)));
+    }
+
+    #[test]
+    fn css_tokens_map_onto_roles() {
+        let theme = Theme::from_tokens(&css_tokens(CSS));
+        assert_eq!(theme.palette, Some(Palette::Gradient(vec![Rgb(170, 0, 68), Rgb(255, 204, 0)])));
+        assert_eq!(theme.text, Some(Rgb(255, 255, 255)));
+        assert_eq!(theme.text_background, Some(Rgb(10, 20, 30)));
+        assert_eq!(theme.accent, Some(Rgb(255, 204, 0)));
+    }
+
+    #[test]
+    fn css_comments_are_skipped() {
+        let tokens = css_tokens(CSS);
+        assert_eq!(tokens.iter().filter(|(name, _)| name == "text-color").count(), 1);
+    }
+
+    #[test]
+    fn one_brand_color_makes_a_gradient_from_dark() {
+        let theme = Theme::from_tokens(&[("brand".to_string(), "#ffffff".to_string())]);
+        assert_eq!(theme.palette, Some(Palette::Gradient(vec![shade(Rgb(255, 255, 255), 0.2), Rgb(255, 255, 255)])));
+    }
+
+    #[test]
+    fn loading_a_file_without_colors_fails() {
+        let path = std::env::temp_dir().join(format!("reveal-theme-{}.css", std::process::id()));
+        fs::write(&path, ":root { --spacing: 4px; }").unwrap();
+        let result = Theme::load(&path);
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(result, Err(LoadError::Invalid { .. })));
+    }
+}
@@ -0,0 +1,116 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+use clap::ValueEnum;
+use serde::Deserialize;
+use termion::color;
+
+use crate::buffer::Buffer;
+use crate::terminal::Cell;
+
+#[derive(ValueEnum, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum TransitionKind {
+    Crossfade,
+    WipeLeft,
+    WipeRight,
+    WipeRadial,
+    Dissolve,
+    Pixelate,
+}
+
+impl FromStr for TransitionKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        <Self as ValueEnum>::from_str(s, true).map_err(|_| format!("unknown transition '{}'", s))
+    }
+}
+
+/// How one scene hands over to the next.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transition {
+    pub kind: TransitionKind,
+    pub duration: Duration,
+}
+
+impl Transition {
+    /// Blends `from` into `to` at `progress` (0.0 = all `from`, 1.0 = all `to`).
+    pub fn composite(&self, from: &Buffer, to: &Buffer, progress: f32, out: &mut Buffer) {
+        let progress = progress.clamp(0.0, 1.0);
+        let (width, height) = (out.width, out.height);
+
+        for y in 0..height {
+            for x in 0..width {
+                let cell = match self.kind {
+                    TransitionKind::Crossfade => crossfade(from.get(x, y), to.get(x, y), progress),
+                    TransitionKind::WipeLeft => {
+                        // The incoming scene enters from the right edge.
+                        let edge = width as f32 * (1.0 - progress);
+                        *pick(from, to, x, y, x as f32 >= edge)
+                    }
+                    TransitionKind::WipeRight => {
+                        let edge = width as f32 * progress;
+                        *pick(from, to, x, y, (x as f32) < edge)
+                    }
+                    TransitionKind::WipeRadial => {
+                        // Cells are roughly twice as tall as they are wide.
+                        let (cx, cy) = (width as f32 / 2.0, height as f32 / 2.0);
+                        let dx = x as f32 - cx;
+                        let dy = (y as f32 - cy) * 2.0;
+                        let max = (cx * cx + (cy * 2.0) * (cy * 2.0)).sqrt();
+                        *pick(from, to, x, y, (dx * dx + dy * dy).sqrt() <= max * progress)
+                    }
+                    TransitionKind::Dissolve => *pick(from, to, x, y, noise(x, y) < progress),
+                    TransitionKind::Pixelate => pixelate(from, to, x, y, progress),
+                };
+                out.set(x, y, cell);
+            }
+        }
+    }
+}
+
+fn pick<'a>(from: &'a Buffer, to: &'a Buffer, x: u16, y: u16, incoming: bool) -> &'a Cell {
+    if incoming {
+        to.get(x, y)
+    } else {
+        from.get(x, y)
+    }
+}
+
+fn crossfade(from: &Cell, to: &Cell, progress: f32) -> Cell {
+    let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * progress).round() as u8;
+    Cell {
+        char: if progress < 0.5 { from.char } else { to.char },
+        background: if progress < 0.5 { from.background } else { to.background },
+        color: color::Rgb(
+            mix(from.color.0, to.color.0),
+            mix(from.color.1, to.color.1),
+            mix(from.color.2, to.color.2),
+        ),
+    }
+}
+
+/// Blocks grow over the outgoing scene, then shrink away over the incoming one.
+fn pixelate(from: &Buffer, to: &Buffer, x: u16, y: u16, progress: f32) -> Cell {
+    const MAX_BLOCK: f32 = 8.0;
+
+    let (source, coarseness) = if progress < 0.5 {
+        (from, progress * 2.0)
+    } else {
+        (to, (1.0 - progress) * 2.0)
+    };
+    let block = (1.0 + coarseness * (MAX_BLOCK - 1.0)).round() as u16;
+    // Terminal cells are tall, so blocks are twice as wide as they are high.
+    let (bw, bh) = if block > 1 { (block * 2, block) } else { (1, 1) };
+    *source.get(x - x % bw, y - y % bh)
+}
+
+/// A stable per-cell value in `[0, 1)`, so dissolved cells don't flicker.
+fn noise(x: u16, y: u16) -> f32 {
+    let mut h = (x as u32).wrapping_mul(0x9E37_79B1) ^ (y as u32).wrapping_mul(0x85EB_CA77);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x2C1B_3C6D);
+    h ^= h >> 12;
+    (h & 0xFFFF) as f32 / 65536.0
+}
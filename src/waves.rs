@@ -1,142 +1,101 @@
-use std::{
-    io::{stdout, Write},
-    thread::sleep,
-    time::{Duration, Instant},
-};
-use termion::{
-    clear, color,
-    cursor::{self},
-};
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
-
-use crate::terminal::Terminal;
+use std::io;
+use std::time::Duration;
+use termion::color;
+use std::sync::mpsc::Receiver;
+
+use crate::animation::Animation;
+use crate::buffer::Buffer;
+use crate::cli::AnimationStyle;
+use crate::control::Control;
+use crate::color::shade;
+use crate::palette::Palette;
+use crate::player::{play, PlayOptions, Scene};
+use crate::terminal::Cell;
 
 use crate::helper::hsv_to_rgb;
 
-pub fn waves_animation(text: &str, duration: Duration, term_signal: &Arc<AtomicBool>) {
-    let start = Instant::now();
-    let term = Terminal::new();
-    let mut stdout = stdout();
-    let (center_x, center_y) = term.center_pos();
-    
-    // Calculate text boundaries
-    let text_start_x = center_x - (text.len() as u16 / 2);
-    let text_end_x = text_start_x + text.len() as u16;
-
-    write!(
-        stdout,
-        "{}{}{}",
-        termion::cursor::Hide,
-        clear::All,
-        termion::screen::ToAlternateScreen
-    ).unwrap();
-
-    while duration.as_millis() == 0 || start.elapsed() < duration  {
-        if term_signal.load(Ordering::Relaxed) {
-            return;  // Just return, cleanup handled in main
-        }
-        
-        write!(stdout, "{}", cursor::Goto(1, 1)).unwrap();
-        let time = start.elapsed().as_secs_f32();
-        
-        for y in 1..=term.height {
-            for x in 1..=term.width {
-                // Skip the area where the text is when on the text's row
-                if y == center_y && x >= text_start_x && x <= text_end_x {
-                    continue;
-                }
-
-                let wave = (x as f32 * 0.1 + time * 2.0).sin();
-                let wave2 = (y as f32 * 0.1 + time * 1.5).cos();
+#[derive(Default)]
+pub struct Waves {
+    palette: Option<Palette>,
+}
+
+impl Animation for Waves {
+    fn render(&mut self, buffer: &mut Buffer, elapsed: Duration) {
+        let time = elapsed.as_secs_f32();
+
+        for y in 0..buffer.height {
+            for x in 0..buffer.width {
+                let wave = ((x + 1) as f32 * 0.1 + time * 2.0).sin();
+                let wave2 = ((y + 1) as f32 * 0.1 + time * 1.5).cos();
                 let combined = wave + wave2;
-                
-                let blue = (((combined + 2.0) / 4.0) * 255.0) as u8;
-                
-                write!(
-                    stdout,
-                    "{}{}▓",
-                    cursor::Goto(x, y),
-                    color::Fg(color::Rgb(0, 0, blue))
-                ).unwrap();
+
+                let level = (combined + 2.0) / 4.0;
+                let color = match &self.palette {
+                    Some(palette) => palette.sample(level),
+                    None => color::Rgb(0, 0, (level * 255.0) as u8),
+                };
+
+                buffer.set(x, y, Cell { char: '▓', color, background: None });
             }
         }
+    }
+
+    fn frame_interval(&self) -> Duration {
+        Duration::from_millis(32)
+    }
 
-        // Draw text only once per frame
-        write!(
-            stdout,
-            "{}{}{}",
-            cursor::Goto(text_start_x, center_y),
-            color::Fg(color::White),
-            text
-        ).unwrap();
-        
-        stdout.flush().unwrap();
-        sleep(Duration::from_millis(32));
+    fn set_palette(&mut self, palette: &Palette) {
+        self.palette = Some(palette.clone());
     }
 }
 
-pub fn waves_gradient_animation(text: &str, duration: Duration, term_signal: &Arc<AtomicBool>) {
-    let start = Instant::now();
-    let term = Terminal::new();
-    let mut stdout = stdout();
-    let (center_x, center_y) = term.center_pos();
-    
-    // Calculate text boundaries
-    let text_start_x = center_x - (text.len() as u16 / 2);
-    let text_end_x = text_start_x + text.len() as u16;
-
-    write!(
-        stdout,
-        "{}{}{}",
-        termion::cursor::Hide,
-        clear::All,
-        termion::screen::ToAlternateScreen
-    ).unwrap();
-
-    while duration.as_millis() == 0 || start.elapsed() < duration {
-        if term_signal.load(Ordering::Relaxed) {
-            return;
-        }
-        
-        write!(stdout, "{}", cursor::Goto(1, 1)).unwrap();
-        let time = start.elapsed().as_secs_f32();
-        
-        for y in 1..=term.height {
-            for x in 1..=term.width {
-                if y == center_y && x >= text_start_x && x <= text_end_x {
-                    continue;
-                }
-
-                let wave = (x as f32 * 0.1 + time * 2.0).sin();
-                let wave2 = (y as f32 * 0.1 + time * 1.5).cos();
+#[derive(Default)]
+pub struct WavesGradient {
+    palette: Option<Palette>,
+}
+
+impl Animation for WavesGradient {
+    fn render(&mut self, buffer: &mut Buffer, elapsed: Duration) {
+        let time = elapsed.as_secs_f32();
+
+        for y in 0..buffer.height {
+            for x in 0..buffer.width {
+                let (fx, fy) = ((x + 1) as f32, (y + 1) as f32);
+                let wave = (fx * 0.1 + time * 2.0).sin();
+                let wave2 = (fy * 0.1 + time * 1.5).cos();
                 let combined = wave + wave2;
-                
+
                 // Create rainbow effect
-                let hue = (time * 0.2 + (x as f32 * 0.02) + (y as f32 * 0.02)) % 1.0;
+                let hue = (time * 0.2 + (fx * 0.02) + (fy * 0.02)) % 1.0;
                 let saturation = 0.8;
                 let value = ((combined + 2.0) / 4.0) * 0.8 + 0.2; // Keep some minimum brightness
-                
-                let (r, g, b) = hsv_to_rgb(hue, saturation, value);
-                
-                write!(
-                    stdout,
-                    "{}{}▓",
-                    cursor::Goto(x, y),
-                    color::Fg(color::Rgb(r, g, b))
-                ).unwrap();
+
+                let color = match &self.palette {
+                    Some(palette) => shade(palette.sample_cyclic(hue), value),
+                    None => {
+                        let (r, g, b) = hsv_to_rgb(hue, saturation, value);
+                        color::Rgb(r, g, b)
+                    }
+                };
+
+                buffer.set(x, y, Cell { char: '▓', color, background: None });
             }
         }
+    }
+
+    fn frame_interval(&self) -> Duration {
+        Duration::from_millis(32)
+    }
 
-        write!(
-            stdout,
-            "{}{}{}",
-            cursor::Goto(text_start_x, center_y),
-            color::Fg(color::White),
-            text
-        ).unwrap();
-        
-        stdout.flush().unwrap();
-        sleep(Duration::from_millis(32));
+    fn set_palette(&mut self, palette: &Palette) {
+        self.palette = Some(palette.clone());
     }
-}
\ No newline at end of file
+}
+
+pub fn waves_animation(text: &str, duration: Duration, controls: &Receiver<Control>) -> io::Result<()> {
+    play(&[Scene::new(AnimationStyle::Waves, text, duration)], &PlayOptions::default(), controls)
+}
+
+pub fn waves_gradient_animation(text: &str, duration: Duration, controls: &Receiver<Control>) -> io::Result<()> {
+    play(&[Scene::new(AnimationStyle::WavesGradient, text, duration)], &PlayOptions::default(), controls)
+}
@@ -0,0 +1,145 @@
+use proptest::prelude::*;
+use reveal::color::{
+    contrast_ratio, hsl_to_rgb, hsv_to_rgb, linear_to_srgb, mix, mix_oklab, parse_hex, relative_luminance,
+    rgb_to_hsl, rgb_to_hsv, srgb_to_linear, to_hex, Oklab, Oklch,
+};
+use reveal::Palette;
+use termion::color::Rgb;
+
+fn rgb() -> impl Strategy<Value = Rgb> {
+    any::<(u8, u8, u8)>().prop_map(|(r, g, b)| Rgb(r, g, b))
+}
+
+/// Any float at all, including NaN, infinities and huge values.
+fn wild() -> impl Strategy<Value = f32> {
+    prop_oneof![
+        -2.0f32..3.0,
+        any::<f32>(),
+        Just(f32::NAN),
+        Just(f32::INFINITY),
+        Just(f32::NEG_INFINITY),
+    ]
+}
+
+fn assert_near(a: Rgb, b: Rgb, tolerance: u8) {
+    let near = |x: u8, y: u8| x.abs_diff(y) <= tolerance;
+    assert!(
+        near(a.0, b.0) && near(a.1, b.1) && near(a.2, b.2),
+        "{:?} and {:?} differ by more than {}",
+        a,
+        b,
+        tolerance
+    );
+}
+
+proptest! {
+    #[test]
+    fn hex_round_trips(color in rgb()) {
+        prop_assert_eq!(parse_hex(&to_hex(color)).unwrap(), color);
+    }
+
+    #[test]
+    fn hex_parsing_never_panics(s in "\\PC{0,10}") {
+        let _ = parse_hex(&s);
+    }
+
+    #[test]
+    fn linear_light_round_trips(c in any::<u8>()) {
+        prop_assert_eq!(linear_to_srgb(srgb_to_linear(c)), c);
+    }
+
+    #[test]
+    fn hsv_round_trips(color in rgb()) {
+        let (h, s, v) = rgb_to_hsv(color);
+        assert_near(hsv_to_rgb(h, s, v), color, 1);
+    }
+
+    #[test]
+    fn hsl_round_trips(color in rgb()) {
+        let (h, s, l) = rgb_to_hsl(color);
+        assert_near(hsl_to_rgb(h, s, l), color, 1);
+    }
+
+    #[test]
+    fn oklab_round_trips(color in rgb()) {
+        assert_near(Oklab::from_rgb(color).to_rgb(), color, 1);
+    }
+
+    #[test]
+    fn oklch_round_trips(color in rgb()) {
+        assert_near(Oklch::from_rgb(color).to_rgb(), color, 1);
+    }
+
+    #[test]
+    fn conversions_clamp_instead_of_panicking(h in wild(), s in wild(), v in wild()) {
+        hsv_to_rgb(h, s, v);
+        hsl_to_rgb(h, s, v);
+        reveal::helper::hsv_to_rgb(h as f64, s as f64, v as f64);
+        Oklab { l: h, a: s, b: v }.to_rgb();
+        Oklch { l: h, c: s, h: v }.to_rgb();
+        linear_to_srgb(h);
+    }
+
+    #[test]
+    fn hue_wraps_around(h in -3.0f32..3.0, s in 0.0f32..=1.0, v in 0.0f32..=1.0) {
+        assert_near(hsv_to_rgb(h, s, v), hsv_to_rgb(h + 1.0, s, v), 1);
+    }
+
+    #[test]
+    fn mixing_keeps_the_ends(from in rgb(), to in rgb()) {
+        prop_assert_eq!(mix(from, to, 0.0), from);
+        prop_assert_eq!(mix(from, to, 1.0), to);
+        prop_assert_eq!(mix_oklab(from, to, 0.0), from);
+        prop_assert_eq!(mix_oklab(from, to, 1.0), to);
+    }
+
+    #[test]
+    fn mixing_clamps_t(from in rgb(), to in rgb(), t in wild()) {
+        let mixed = mix(from, to, t);
+        if t <= 0.0 || t.is_nan() {
+            prop_assert_eq!(mixed, from);
+        } else if t >= 1.0 {
+            prop_assert_eq!(mixed, to);
+        }
+        mix_oklab(from, to, t);
+    }
+
+    #[test]
+    fn mixing_in_linear_light_moves_luminance_steadily(from in rgb(), to in rgb(), t in 0.0f32..=1.0) {
+        let (a, b) = (relative_luminance(from), relative_luminance(to));
+        let mixed = relative_luminance(mix(from, to, t));
+        prop_assert!(mixed >= a.min(b) - 0.01 && mixed <= a.max(b) + 0.01);
+    }
+
+    #[test]
+    fn contrast_ratio_is_symmetric_and_bounded(a in rgb(), b in rgb()) {
+        let ratio = contrast_ratio(a, b);
+        prop_assert!((1.0..=21.0 + 1e-3).contains(&ratio));
+        prop_assert!((ratio - contrast_ratio(b, a)).abs() < 1e-5);
+        prop_assert!((contrast_ratio(a, a) - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn palettes_sample_any_position(t in wild(), i in any::<usize>()) {
+        for name in Palette::NAMES {
+            let palette = Palette::named(name).unwrap();
+            palette.sample(t);
+            palette.sample_cyclic(t);
+            palette.cycle(i);
+        }
+    }
+}
+
+#[test]
+fn black_on_white_has_the_highest_contrast() {
+    let ratio = contrast_ratio(Rgb(0, 0, 0), Rgb(255, 255, 255));
+    assert!((ratio - 21.0).abs() < 1e-3);
+}
+
+#[test]
+fn short_hex_expands() {
+    assert_eq!(parse_hex("#f80").unwrap(), Rgb(255, 136, 0));
+    assert_eq!(parse_hex("00ff7f").unwrap(), Rgb(0, 255, 127));
+    assert!(parse_hex("#ff00").is_err());
+    assert!(parse_hex("#gg0000").is_err());
+}
@@ -0,0 +1,24 @@
+use std::time::Duration;
+
+use clap::ValueEnum;
+use reveal::{AnimationStyle, Reveal};
+
+/// The frame after `ticks` steps of `step` each.
+fn frame(style: &AnimationStyle, step: Duration, ticks: u32) -> Reveal {
+    let mut reveal = Reveal::builder().style(style.clone()).text("Hi").size(30, 10).seed(7).build();
+    for _ in 0..ticks {
+        reveal.tick(step);
+    }
+    reveal
+}
+
+#[test]
+fn every_style_moves_at_the_same_speed_at_any_frame_rate() {
+    for style in AnimationStyle::value_variants() {
+        let fast = frame(style, Duration::from_millis(16), 60);
+        let slow = frame(style, Duration::from_millis(32), 30);
+        let dropped = frame(style, Duration::from_millis(480), 2);
+        assert!(fast.cells() == slow.cells(), "{:?} differs between 60 and 30 fps", style);
+        assert!(fast.cells() == dropped.cells(), "{:?} differs when frames are dropped", style);
+    }
+}